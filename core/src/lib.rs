@@ -12,4 +12,4 @@ pub mod utils;
 pub use models::{AppError, AppSettings, Feedback, ProjectSettings};
 pub use services::{ConversationStorage, EventBusImpl, ProcessManagerImpl, SettingsManagerImpl};
 pub use traits::{Command, EventBus, ProcessManager, SettingsManager, Tool};
-pub use utils::{error_handling, init_logging};
+pub use utils::{error_handling, init_logging, init_logging_with_layer};
@@ -1,9 +1,12 @@
 //! Feedback model and related types
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::path::PathBuf;
 use std::time::{Duration, SystemTime};
 
+use crate::traits::ValidationError;
+
 /// Feedback data structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Feedback {
@@ -21,7 +24,6 @@ pub struct Feedback {
 pub struct FeedbackMetadata {
     pub tool_name: String,
     pub execution_time: Duration,
-    pub commands_executed: Vec<String>,
     pub user_interaction_time: Duration,
 }
 
@@ -30,7 +32,6 @@ impl Default for FeedbackMetadata {
         Self {
             tool_name: "interactive_feedback".to_string(),
             execution_time: Duration::from_secs(0),
-            commands_executed: vec![],
             user_interaction_time: Duration::from_secs(0),
         }
     }
@@ -60,11 +61,6 @@ impl Feedback {
         self.interactive_feedback = feedback;
     }
 
-    /// Add executed command
-    pub fn add_executed_command(&mut self, command: String) {
-        self.metadata.commands_executed.push(command);
-    }
-
     /// Set execution time
     pub fn set_execution_time(&mut self, duration: Duration) {
         self.metadata.execution_time = duration;
@@ -80,3 +76,190 @@ impl Feedback {
         self.conversation_history = history;
     }
 }
+
+/// Version of the envelopes exchanged with the feedback window process
+pub const FEEDBACK_PROTOCOL_VERSION: u32 = 1;
+
+/// Reject an envelope written for another protocol version, which happens
+/// when the server and the feedback window come from different builds
+pub fn check_feedback_protocol_version(version: u32) -> Result<(), ValidationError> {
+    if version == FEEDBACK_PROTOCOL_VERSION {
+        return Ok(());
+    }
+    Err(ValidationError::InvalidInput {
+        message: format!(
+            "feedback protocol version {} is not supported, expected {}",
+            version, FEEDBACK_PROTOCOL_VERSION
+        ),
+    })
+}
+
+/// Request passed to the feedback window process on stdin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedbackRequest {
+    pub version: u32,
+    pub project_directory: PathBuf,
+    pub prompt: String,
+    /// Close the window with a timeout outcome after this many seconds
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>,
+    /// Quick-reply choices shown above the text box
+    #[serde(default)]
+    pub options: Vec<FeedbackOption>,
+    /// Whether the user may type a reply instead of picking an option
+    #[serde(default = "default_allow_free_text")]
+    pub allow_free_text: bool,
+    /// Structured fields to fill in alongside the reply
+    #[serde(default)]
+    pub form: Option<super::FormSchema>,
+}
+
+fn default_allow_free_text() -> bool {
+    true
+}
+
+/// Quick-reply choice offered in the feedback window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedbackOption {
+    pub label: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Preselected in the window and used when the round times out
+    #[serde(default)]
+    pub default: bool,
+}
+
+impl FeedbackRequest {
+    /// Create a new feedback request
+    pub fn new(project_directory: PathBuf, prompt: String) -> Self {
+        Self {
+            version: FEEDBACK_PROTOCOL_VERSION,
+            project_directory,
+            prompt,
+            timeout_seconds: None,
+            options: vec![],
+            allow_free_text: true,
+            form: None,
+        }
+    }
+
+    /// The option marked as default, if any
+    pub fn default_option(&self) -> Option<&FeedbackOption> {
+        self.options.iter().find(|option| option.default)
+    }
+
+    /// Time after which the window gives up waiting for the user
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_seconds.map(Duration::from_secs)
+    }
+}
+
+/// How a feedback round ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedbackOutcome {
+    Submitted,
+    Cancelled,
+    Timeout,
+}
+
+/// File attached to a feedback response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedbackAttachment {
+    pub path: PathBuf,
+    pub mime_type: String,
+}
+
+impl FeedbackAttachment {
+    /// MIME types of the images that can be attached
+    pub const IMAGE_MIME_TYPES: [&'static str; 2] = ["image/png", "image/jpeg"];
+
+    /// Detect a supported image type from the file's magic bytes
+    pub fn sniff_image_mime_type(bytes: &[u8]) -> Option<&'static str> {
+        if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some("image/png")
+        } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some("image/jpeg")
+        } else {
+            None
+        }
+    }
+
+    /// File extension for a supported image type
+    pub fn extension_for(mime_type: &str) -> Option<&'static str> {
+        match mime_type {
+            "image/png" => Some("png"),
+            "image/jpeg" => Some("jpg"),
+            _ => None,
+        }
+    }
+}
+
+/// State of the feedback window while the user answers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedbackStatus {
+    /// The window is shown
+    Opened,
+    /// The user is writing a reply
+    Typing,
+}
+
+/// Status envelope written by the feedback window process to stdout,
+/// one line per change, before the result envelope
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedbackStatusUpdate {
+    pub version: u32,
+    pub status: FeedbackStatus,
+}
+
+impl FeedbackStatusUpdate {
+    /// Create an update for the given status
+    pub fn new(status: FeedbackStatus) -> Self {
+        Self {
+            version: FEEDBACK_PROTOCOL_VERSION,
+            status,
+        }
+    }
+}
+
+/// Result envelope written by the feedback window process to stdout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedbackResult {
+    pub version: u32,
+    pub outcome: FeedbackOutcome,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub attachments: Vec<FeedbackAttachment>,
+    /// Label of the quick-reply option the user picked
+    #[serde(default)]
+    pub selected_option: Option<String>,
+    /// Values entered in the form, keyed by field name
+    #[serde(default)]
+    pub form_response: Option<Map<String, Value>>,
+    /// Output of the commands run from the command section
+    #[serde(default)]
+    pub command_logs: String,
+    /// Command lines run from the command section, in order
+    #[serde(default)]
+    pub commands_executed: Vec<String>,
+    pub interaction_duration_ms: u64,
+}
+
+impl FeedbackResult {
+    /// Create a result for the given outcome
+    pub fn new(outcome: FeedbackOutcome, text: String, interaction_duration: Duration) -> Self {
+        Self {
+            version: FEEDBACK_PROTOCOL_VERSION,
+            outcome,
+            text,
+            attachments: vec![],
+            selected_option: None,
+            form_response: None,
+            command_logs: String::new(),
+            commands_executed: vec![],
+            interaction_duration_ms: interaction_duration.as_millis() as u64,
+        }
+    }
+}
@@ -0,0 +1,288 @@
+//! Feedback form definitions
+//!
+//! Forms use the primitive subset of JSON Schema that MCP elicitation
+//! (`requestedSchema`) accepts: a flat object whose properties are strings,
+//! string enums, numbers, integers or booleans.
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::traits::ValidationError;
+
+/// Form made of flat, primitive fields
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormSchema {
+    #[serde(rename = "type", default = "default_schema_type")]
+    pub schema_type: String,
+    /// Fields in display order
+    pub properties: IndexMap<String, FormField>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required: Vec<String>,
+}
+
+fn default_schema_type() -> String {
+    "object".to_string()
+}
+
+/// Single form field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormField {
+    #[serde(rename = "type")]
+    pub field_type: FormFieldType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Allowed values; only valid for string fields
+    #[serde(rename = "enum", default, skip_serializing_if = "Option::is_none")]
+    pub enum_values: Option<Vec<String>>,
+    /// Display names matching `enum_values`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enum_names: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+}
+
+/// Primitive field types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FormFieldType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+}
+
+impl FormField {
+    /// Label shown next to the field
+    pub fn label<'a>(&'a self, name: &'a str) -> &'a str {
+        self.title.as_deref().unwrap_or(name)
+    }
+
+    /// Display name of an enum value
+    pub fn enum_label(&self, index: usize) -> Option<&str> {
+        let values = self.enum_values.as_ref()?;
+        self.enum_names
+            .as_ref()
+            .and_then(|names| names.get(index))
+            .or_else(|| values.get(index))
+            .map(String::as_str)
+    }
+
+    /// Check a single value against this field
+    pub fn validate_value(&self, name: &str, value: &Value) -> Result<(), ValidationError> {
+        let invalid_type = |expected: &str| ValidationError::InvalidType {
+            field: name.to_string(),
+            expected: expected.to_string(),
+        };
+
+        match self.field_type {
+            FormFieldType::String => {
+                let text = value.as_str().ok_or_else(|| invalid_type("string"))?;
+                if let Some(values) = &self.enum_values {
+                    if !values.iter().any(|v| v == text) {
+                        return Err(ValidationError::InvalidInput {
+                            message: format!("{} must be one of: {}", name, values.join(", ")),
+                        });
+                    }
+                }
+                let length = text.chars().count();
+                if self.min_length.is_some_and(|min| length < min)
+                    || self.max_length.is_some_and(|max| length > max)
+                {
+                    return Err(ValidationError::OutOfRange {
+                        field: name.to_string(),
+                    });
+                }
+            }
+            FormFieldType::Number | FormFieldType::Integer => {
+                let number = if self.field_type == FormFieldType::Integer {
+                    value.as_i64().map(|n| n as f64)
+                } else {
+                    value.as_f64()
+                };
+                let number = number.ok_or_else(|| {
+                    invalid_type(if self.field_type == FormFieldType::Integer {
+                        "integer"
+                    } else {
+                        "number"
+                    })
+                })?;
+                if self.minimum.is_some_and(|min| number < min)
+                    || self.maximum.is_some_and(|max| number > max)
+                {
+                    return Err(ValidationError::OutOfRange {
+                        field: name.to_string(),
+                    });
+                }
+            }
+            FormFieldType::Boolean => {
+                value.as_bool().ok_or_else(|| invalid_type("boolean"))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FormSchema {
+    /// Check that the form itself is well formed
+    pub fn validate_definition(&self) -> Result<(), ValidationError> {
+        if self.schema_type != "object" {
+            return Err(ValidationError::InvalidInput {
+                message: "form type must be \"object\"".to_string(),
+            });
+        }
+
+        if self.properties.is_empty() {
+            return Err(ValidationError::InvalidInput {
+                message: "form must define at least one property".to_string(),
+            });
+        }
+
+        for name in &self.required {
+            if !self.properties.contains_key(name) {
+                return Err(ValidationError::InvalidInput {
+                    message: format!("required field {} is not defined", name),
+                });
+            }
+        }
+
+        for (name, field) in &self.properties {
+            if let Some(values) = &field.enum_values {
+                if field.field_type != FormFieldType::String || values.is_empty() {
+                    return Err(ValidationError::InvalidInput {
+                        message: format!("{}: enum must be a non-empty list of strings", name),
+                    });
+                }
+                if field
+                    .enum_names
+                    .as_ref()
+                    .is_some_and(|names| names.len() != values.len())
+                {
+                    return Err(ValidationError::InvalidInput {
+                        message: format!("{}: enumNames must match enum", name),
+                    });
+                }
+            }
+
+            if let Some(default) = &field.default {
+                field.validate_value(name, default)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check a submitted response against the form
+    pub fn validate_response(&self, response: &Map<String, Value>) -> Result<(), ValidationError> {
+        for name in &self.required {
+            if !response.contains_key(name) {
+                return Err(ValidationError::MissingField {
+                    field: self.properties[name].label(name).to_string(),
+                });
+            }
+        }
+
+        for (name, value) in response {
+            let field = self
+                .properties
+                .get(name)
+                .ok_or_else(|| ValidationError::InvalidInput {
+                    message: format!("Unknown form field: {}", name),
+                })?;
+            field.validate_value(name, value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether a field must be filled in
+    pub fn is_required(&self, name: &str) -> bool {
+        self.required.iter().any(|required| required == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> FormSchema {
+        serde_json::from_value(json!({
+            "type": "object",
+            "properties": {
+                "priority": { "type": "string", "title": "Priority", "enum": ["low", "high"] },
+                "estimate": { "type": "integer", "minimum": 1 },
+                "notify": { "type": "boolean" }
+            },
+            "required": ["priority"]
+        }))
+        .unwrap()
+    }
+
+    fn response(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn valid_responses_are_accepted() {
+        let schema = schema();
+        assert!(schema.validate_definition().is_ok());
+        assert!(schema
+            .validate_response(&response(json!({ "priority": "high" })))
+            .is_ok());
+        assert!(schema
+            .validate_response(&response(
+                json!({ "priority": "low", "estimate": 3, "notify": true })
+            ))
+            .is_ok());
+    }
+
+    #[test]
+    fn missing_required_fields_are_reported_by_label() {
+        let error = schema()
+            .validate_response(&response(json!({ "estimate": 3 })))
+            .unwrap_err();
+        assert!(
+            matches!(&error, ValidationError::MissingField { field } if field == "Priority"),
+            "{:?}",
+            error
+        );
+    }
+
+    #[test]
+    fn values_of_the_wrong_type_are_rejected() {
+        let schema = schema();
+        for (name, value, expected) in [
+            ("estimate", json!("3"), "integer"),
+            ("estimate", json!(2.5), "integer"),
+            ("notify", json!("yes"), "boolean"),
+            ("priority", json!(1), "string"),
+        ] {
+            let mut submitted = response(json!({ "priority": "low" }));
+            submitted.insert(name.to_string(), value);
+            let error = schema.validate_response(&submitted).unwrap_err();
+            assert!(
+                matches!(
+                    &error,
+                    ValidationError::InvalidType { field, expected: e } if field == name && e == expected
+                ),
+                "{:?}",
+                error
+            );
+        }
+    }
+}
@@ -2,11 +2,13 @@
 
 pub mod error;
 pub mod feedback;
+pub mod form;
 pub mod project;
 pub mod settings;
 
 // Re-export all models
 pub use error::*;
 pub use feedback::*;
+pub use form::*;
 pub use project::*;
 pub use settings::*;
@@ -15,9 +15,11 @@ pub struct Project {
 
 /// Project-specific settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ProjectSettings {
-    pub auto_execute: bool,
     pub default_command: Option<String>,
+    /// Run commands on a pseudo-terminal, keeping colors and accepting input
+    pub use_pty: bool,
     pub ui_state: UIState,
 }
 
@@ -40,8 +42,8 @@ pub struct ProjectMetadata {
 impl Default for ProjectSettings {
     fn default() -> Self {
         Self {
-            auto_execute: false,
             default_command: None,
+            use_pty: false,
             ui_state: UIState {
                 window_size: (800, 600),
                 window_position: (100, 100),
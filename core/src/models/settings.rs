@@ -1,6 +1,7 @@
 //! Application settings model
 
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::time::Duration;
 
 /// Application settings
@@ -18,6 +19,9 @@ pub struct GeneralSettings {
     pub log_level: LogLevel,
     pub timeout: Duration,
     pub auto_save: bool,
+    /// Reply returned to the agent when a feedback window times out
+    #[serde(default = "default_timeout_response")]
+    pub timeout_response: String,
 }
 
 /// UI settings
@@ -32,9 +36,72 @@ pub struct UISettings {
 /// Security settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecuritySettings {
+    /// Programs that may run; shorthand for an allow rule per program
     pub allowed_commands: Vec<String>,
+    /// Rules deciding which command lines may run
+    #[serde(default)]
+    pub command_policy: CommandPolicySettings,
     pub sandbox_mode: bool,
     pub max_process_time: Duration,
+    /// Directories project directories must be inside; empty allows any
+    #[serde(default)]
+    pub allowed_roots: Vec<PathBuf>,
+    /// Also accept project directories inside the roots the MCP client exposes
+    #[serde(default = "default_use_client_roots")]
+    pub use_client_roots: bool,
+    /// Commands that start as soon as their project's feedback window opens
+    ///
+    /// Kept in the user's settings rather than the project's, so a cloned
+    /// repository cannot make the window run anything by itself.
+    #[serde(default)]
+    pub auto_run_commands: Vec<AutoRunCommand>,
+}
+
+/// Command the user chose to run whenever a project's feedback window opens
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutoRunCommand {
+    pub project_directory: PathBuf,
+    pub command: String,
+}
+
+/// Allow and deny rules for command lines
+///
+/// Deny rules win over allow rules. When no rule matches, `default_action`
+/// decides; if it is not set, commands are denied as soon as any allow rule
+/// (or `allowed_commands` entry) exists, and allowed otherwise.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommandPolicySettings {
+    pub rules: Vec<CommandRule>,
+    pub default_action: Option<PolicyAction>,
+}
+
+/// Rule matching a program and its arguments
+///
+/// `pattern` is split into words like a command line; `*` and `?` work as in
+/// globs. For allow rules the first word is matched against the program as
+/// typed and the following ones against the leading arguments. Further
+/// arguments are not checked, so `git diff` also matches `git diff HEAD~1`.
+///
+/// Deny rules are matched more loosely to make them harder to sidestep: the
+/// program may appear anywhere in the line, by path or by name, its arguments
+/// may have other words in between, and quoted words are split again.
+/// `git push` thus also denies `/usr/bin/git push`, `git -C . push`,
+/// `env git push` and `sh -c "git push"`. This is best-effort; commands
+/// assembled by the program itself, such as `git -c alias.p=push p`, are not
+/// caught, so prefer allow rules with a deny default for untrusted input.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandRule {
+    pub action: PolicyAction,
+    pub pattern: String,
+}
+
+/// What a command rule does with the commands it matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyAction {
+    Allow,
+    Deny,
 }
 
 /// Performance settings
@@ -63,6 +130,14 @@ pub enum Theme {
     Auto,
 }
 
+fn default_use_client_roots() -> bool {
+    true
+}
+
+fn default_timeout_response() -> String {
+    "No response from the user, continue with your best judgement".to_string()
+}
+
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
@@ -70,6 +145,7 @@ impl Default for AppSettings {
                 log_level: LogLevel::Info,
                 timeout: Duration::from_secs(300),
                 auto_save: true,
+                timeout_response: default_timeout_response(),
             },
             ui: UISettings {
                 theme: Theme::Auto,
@@ -79,11 +155,16 @@ impl Default for AppSettings {
             },
             security: SecuritySettings {
                 allowed_commands: vec![],
+                command_policy: CommandPolicySettings::default(),
                 sandbox_mode: true,
                 max_process_time: Duration::from_secs(60),
+                allowed_roots: vec![],
+                use_client_roots: default_use_client_roots(),
+                auto_run_commands: vec![],
             },
             performance: PerformanceSettings {
-                max_memory_usage: 100 * 1024 * 1024, // 100MB
+                // 4GB, enough for a cargo build; capped where usize is 32 bits
+                max_memory_usage: (4u64 * 1024 * 1024 * 1024).min(usize::MAX as u64) as usize,
                 log_rotation_size: 10 * 1024 * 1024, // 10MB
                 cache_size: 50 * 1024 * 1024,        // 50MB
             },
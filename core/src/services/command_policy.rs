@@ -0,0 +1,223 @@
+//! Command policy
+//!
+//! Decides whether a command line may run, based on the allow and deny
+//! rules in `SecuritySettings`. Commands are never run through a shell, so
+//! the line is split into words with shell quoting rules and each word is
+//! passed to the program as is.
+
+use std::path::Path;
+
+use wildmatch::WildMatch;
+
+use crate::models::{CommandRule, PolicyAction, SecuritySettings};
+use crate::traits::{ValidationError, ValidationRule};
+
+/// Split a command line into words, honoring quotes and escapes
+pub fn parse_command_line(command_line: &str) -> Result<Vec<String>, ValidationError> {
+    let words = shell_words::split(command_line).map_err(|e| ValidationError::InvalidInput {
+        message: format!("Cannot parse command line: {}", e),
+    })?;
+    if words.is_empty() {
+        return Err(ValidationError::InvalidInput {
+            message: "Command line is empty".to_string(),
+        });
+    }
+    Ok(words)
+}
+
+/// Outcome of checking a command against the policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyDecision {
+    pub allowed: bool,
+    /// Rule that decided, `None` when the default action applied
+    pub matched_rule: Option<CommandRule>,
+    /// Why the command was allowed or denied, for showing to the user
+    pub explanation: String,
+}
+
+/// Rule with its pattern split into word globs
+struct CompiledRule {
+    rule: CommandRule,
+    words: Vec<WildMatch>,
+}
+
+impl CompiledRule {
+    fn new(rule: CommandRule) -> Result<Self, ValidationError> {
+        let words =
+            parse_command_line(&rule.pattern).map_err(|e| ValidationError::InvalidInput {
+                message: format!("Invalid command rule `{}`: {}", rule.pattern, e),
+            })?;
+        Ok(Self {
+            words: words.iter().map(|word| WildMatch::new(word)).collect(),
+            rule,
+        })
+    }
+
+    fn matches(&self, words: &[String]) -> bool {
+        match self.rule.action {
+            PolicyAction::Allow => self.matches_leading(words),
+            PolicyAction::Deny => self.matches_within(words),
+        }
+    }
+
+    /// The program as typed, then the leading arguments by position
+    fn matches_leading(&self, words: &[String]) -> bool {
+        self.words.len() <= words.len()
+            && self
+                .words
+                .iter()
+                .zip(words)
+                .all(|(pattern, word)| pattern.matches(word))
+    }
+
+    /// The program by name anywhere in the line, followed by the pattern's
+    /// arguments in order with anything in between
+    ///
+    /// This catches `/usr/bin/git push`, `git -C . push`, `env git push` and
+    /// `sh -c "git push"` alike, at the cost of also denying lines that merely
+    /// mention the words. It is best-effort: a program that builds the command
+    /// itself, like `git -c alias.p=push p`, still gets through.
+    fn matches_within(&self, words: &[String]) -> bool {
+        let Some((program, arguments)) = self.words.split_first() else {
+            return false;
+        };
+        let words = inner_words(words);
+        words.iter().enumerate().any(|(position, word)| {
+            if !program.matches(word) && !program.matches(program_name(word)) {
+                return false;
+            }
+            let mut rest = words[position + 1..].iter();
+            arguments
+                .iter()
+                .all(|pattern| rest.any(|word| pattern.matches(word)))
+        })
+    }
+}
+
+/// Words with any containing whitespace split again, so the command line
+/// passed to `sh -c` or similar is checked as well
+fn inner_words(words: &[String]) -> Vec<String> {
+    let mut inner = Vec::with_capacity(words.len());
+    for word in words {
+        if !word.contains(char::is_whitespace) {
+            inner.push(word.clone());
+            continue;
+        }
+        match shell_words::split(word) {
+            Ok(split) => inner.extend(inner_words(&split)),
+            Err(_) => inner.extend(word.split_whitespace().map(str::to_string)),
+        }
+    }
+    inner
+}
+
+/// File name of a program path, e.g. `git` for `/usr/bin/git`
+fn program_name(word: &str) -> &str {
+    Path::new(word)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(word)
+}
+
+/// Allow and deny rules for command lines
+pub struct CommandPolicy {
+    rules: Vec<CompiledRule>,
+    default_action: PolicyAction,
+}
+
+impl CommandPolicy {
+    /// Build the policy from `command_policy`, with `allowed_commands` as
+    /// extra allow rules
+    pub fn from_settings(settings: &SecuritySettings) -> Result<Self, ValidationError> {
+        let shorthand = settings.allowed_commands.iter().map(|program| CommandRule {
+            action: PolicyAction::Allow,
+            pattern: shell_words::quote(program).into_owned(),
+        });
+        let rules = settings
+            .command_policy
+            .rules
+            .iter()
+            .cloned()
+            .chain(shorthand)
+            .map(CompiledRule::new)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let has_allow_rules = rules
+            .iter()
+            .any(|rule| rule.rule.action == PolicyAction::Allow);
+        let default_action = settings
+            .command_policy
+            .default_action
+            .unwrap_or(if has_allow_rules {
+                PolicyAction::Deny
+            } else {
+                PolicyAction::Allow
+            });
+
+        Ok(Self {
+            rules,
+            default_action,
+        })
+    }
+
+    /// Decide on a command split into words; deny rules win over allow rules
+    pub fn evaluate(&self, words: &[String]) -> PolicyDecision {
+        let matching = |action| {
+            self.rules
+                .iter()
+                .find(|rule| rule.rule.action == action && rule.matches(words))
+        };
+        if let Some(rule) = matching(PolicyAction::Deny).or_else(|| matching(PolicyAction::Allow)) {
+            let allowed = rule.rule.action == PolicyAction::Allow;
+            return PolicyDecision {
+                allowed,
+                explanation: format!(
+                    "{} by rule `{} {}`",
+                    if allowed { "Allowed" } else { "Denied" },
+                    if allowed { "allow" } else { "deny" },
+                    rule.rule.pattern
+                ),
+                matched_rule: Some(rule.rule.clone()),
+            };
+        }
+
+        let program = words.first().map(String::as_str).unwrap_or_default();
+        let (allowed, explanation) = match self.default_action {
+            PolicyAction::Allow => (true, format!("Allowed: no rule matches `{}`", program)),
+            PolicyAction::Deny => (
+                false,
+                format!(
+                    "Denied: no allow rule matches `{}`; add one to security.command_policy",
+                    shell_words::join(words)
+                ),
+            ),
+        };
+        PolicyDecision {
+            allowed,
+            matched_rule: None,
+            explanation,
+        }
+    }
+
+    /// Parse and decide on a command line
+    pub fn check(&self, command_line: &str) -> Result<PolicyDecision, ValidationError> {
+        Ok(self.evaluate(&parse_command_line(command_line)?))
+    }
+}
+
+impl ValidationRule for CommandPolicy {
+    fn validate(&self, input: &str) -> Result<(), ValidationError> {
+        let decision = self.check(input)?;
+        if decision.allowed {
+            Ok(())
+        } else {
+            Err(ValidationError::InvalidInput {
+                message: decision.explanation,
+            })
+        }
+    }
+
+    fn rule_name(&self) -> &str {
+        "CommandPolicy"
+    }
+}
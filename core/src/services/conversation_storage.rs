@@ -1,6 +1,6 @@
 //! Conversation storage service for managing user-agent conversation history
 
-use crate::models::AppError;
+use crate::models::{AppError, FeedbackAttachment};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -11,6 +11,11 @@ pub struct ConversationMessage {
     pub role: String, // "user" or "assistant"
     pub content: String,
     pub timestamp: String, // Use string for easier serialization
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cancelled: bool,
+    /// Files saved under `.ifm-ruta/attachments/`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<FeedbackAttachment>,
 }
 
 /// Conversation session
@@ -23,6 +28,42 @@ pub struct ConversationSession {
     pub last_updated: String, // Use string for easier serialization
 }
 
+impl ConversationSession {
+    /// Render the session as a markdown transcript
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("# Conversation {}\n\n", self.session_id);
+        markdown.push_str(&format!(
+            "- Project: `{}`\n",
+            self.project_directory.display()
+        ));
+        markdown.push_str(&format!("- Created: {}\n", self.created_at));
+        markdown.push_str(&format!("- Last updated: {}\n", self.last_updated));
+
+        for message in &self.messages {
+            let role = match message.role.as_str() {
+                "user" => "User",
+                "assistant" => "Assistant",
+                other => other,
+            };
+            markdown.push_str(&format!("\n## {} ({})\n\n", role, message.timestamp));
+            if message.cancelled {
+                markdown.push_str("_Cancelled before the user replied._\n\n");
+            }
+            markdown.push_str(message.content.trim_end());
+            markdown.push('\n');
+            for attachment in &message.attachments {
+                markdown.push_str(&format!(
+                    "\n- Attachment: `{}` ({})\n",
+                    attachment.path.display(),
+                    attachment.mime_type
+                ));
+            }
+        }
+
+        markdown
+    }
+}
+
 /// Storage statistics
 #[derive(Debug, Clone)]
 pub struct StorageStats {
@@ -43,6 +84,16 @@ impl ConversationStorage {
         Self { storage_dir }
     }
 
+    /// File a session is stored in
+    pub fn session_file(&self, session_id: &str) -> PathBuf {
+        self.storage_dir.join(format!("{}.json", session_id))
+    }
+
+    /// Whether any session was ever stored, without creating the directory
+    pub fn exists(&self) -> bool {
+        self.storage_dir.is_dir()
+    }
+
     /// Initialize storage directory
     pub fn initialize(&self) -> Result<(), AppError> {
         if !self.storage_dir.exists() {
@@ -57,9 +108,7 @@ impl ConversationStorage {
     pub fn save_session(&self, session: &ConversationSession) -> Result<(), AppError> {
         self.initialize()?;
 
-        let session_file = self
-            .storage_dir
-            .join(format!("{}.json", session.session_id));
+        let session_file = self.session_file(&session.session_id);
         let content =
             serde_json::to_string_pretty(session).map_err(|e| AppError::SerializationError {
                 message: format!("Failed to serialize session: {}", e),
@@ -74,7 +123,7 @@ impl ConversationStorage {
 
     /// Load a conversation session
     pub fn load_session(&self, session_id: &str) -> Result<Option<ConversationSession>, AppError> {
-        let session_file = self.storage_dir.join(format!("{}.json", session_id));
+        let session_file = self.session_file(session_id);
 
         if !session_file.exists() {
             return Ok(None);
@@ -98,10 +147,10 @@ impl ConversationStorage {
 
         let mut sessions = Vec::new();
 
-        println!("Looking for conversations in: {:?}", self.storage_dir);
+        tracing::debug!("Looking for conversations in: {:?}", self.storage_dir);
 
         if !self.storage_dir.exists() {
-            println!("Storage directory does not exist: {:?}", self.storage_dir);
+            tracing::debug!("Storage directory does not exist: {:?}", self.storage_dir);
             return Ok(sessions);
         }
 
@@ -114,20 +163,25 @@ impl ConversationStorage {
                 message: format!("Failed to read directory entry: {}", e),
             })?;
 
-            println!("Found file: {:?}", entry.path());
+            tracing::trace!("Found file: {:?}", entry.path());
 
             if entry.path().extension().and_then(|s| s.to_str()) == Some("json") {
                 let path = entry.path();
                 let session_id = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
-                println!("Loading session: {}", session_id);
+                tracing::debug!("Loading session: {}", session_id);
 
-                if let Ok(session) = self.load_session(session_id) {
-                    if let Some(session) = session {
-                        println!("Loaded session with {} messages", session.messages.len());
+                match self.load_session(session_id) {
+                    Ok(Some(session)) => {
+                        tracing::debug!("Loaded session with {} messages", session.messages.len());
                         sessions.push(session);
                     }
-                } else {
-                    println!("Failed to load session: {}", session_id);
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!(
+                        session_id,
+                        path = %path.display(),
+                        error = %e,
+                        "Failed to load session"
+                    ),
                 }
             }
         }
@@ -148,9 +202,7 @@ impl ConversationStorage {
             let sessions_to_delete = sessions.split_off(5);
 
             for session in sessions_to_delete {
-                let session_file = self
-                    .storage_dir
-                    .join(format!("{}.json", session.session_id));
+                let session_file = self.session_file(&session.session_id);
                 if session_file.exists() {
                     let _ = fs::remove_file(&session_file);
                 }
@@ -160,8 +212,52 @@ impl ConversationStorage {
         Ok(sessions)
     }
 
+    /// Directory holding files attached to feedback replies
+    pub fn attachments_dir(&self) -> PathBuf {
+        self.storage_dir.with_file_name("attachments")
+    }
+
+    /// Save an attached file under a fresh name in the attachments directory
+    pub fn save_attachment(
+        &self,
+        bytes: &[u8],
+        mime_type: &str,
+    ) -> Result<FeedbackAttachment, AppError> {
+        let extension =
+            FeedbackAttachment::extension_for(mime_type).ok_or_else(|| AppError::StorageError {
+                message: format!("Unsupported attachment type: {}", mime_type),
+            })?;
+
+        let attachments_dir = self.attachments_dir();
+        fs::create_dir_all(&attachments_dir).map_err(|e| AppError::StorageError {
+            message: format!("Failed to create attachments directory: {}", e),
+        })?;
+
+        let path = attachments_dir.join(format!("{}.{}", uuid::Uuid::new_v4(), extension));
+        fs::write(&path, bytes).map_err(|e| AppError::StorageError {
+            message: format!("Failed to write attachment: {}", e),
+        })?;
+
+        Ok(FeedbackAttachment {
+            path,
+            mime_type: mime_type.to_string(),
+        })
+    }
+
     /// Add a message to a conversation session - append to existing or create new
     pub fn add_message(&self, session_id: &str, role: &str, content: &str) -> Result<(), AppError> {
+        self.add_message_with_attachments(session_id, role, content, Vec::new())?;
+        Ok(())
+    }
+
+    /// Add a message that references attached files, returning it as stored
+    pub fn add_message_with_attachments(
+        &self,
+        session_id: &str,
+        role: &str,
+        content: &str,
+        attachments: Vec<FeedbackAttachment>,
+    ) -> Result<ConversationMessage, AppError> {
         let mut session = self.load_session(session_id)?.unwrap_or_else(|| {
             // Create new session only if it doesn't exist
             ConversationSession {
@@ -177,12 +273,34 @@ impl ConversationStorage {
             role: role.to_string(),
             content: content.to_string(),
             timestamp: chrono::Utc::now().to_rfc3339(),
+            cancelled: false,
+            attachments,
         };
 
-        session.messages.push(message);
+        session.messages.push(message.clone());
         session.last_updated = chrono::Utc::now().to_rfc3339();
 
         self.save_session(&session)?;
+        Ok(message)
+    }
+
+    /// Mark the latest assistant message of a session as cancelled
+    pub fn mark_cancelled(&self, session_id: &str) -> Result<(), AppError> {
+        let Some(mut session) = self.load_session(session_id)? else {
+            return Ok(());
+        };
+
+        if let Some(message) = session
+            .messages
+            .iter_mut()
+            .rev()
+            .find(|message| message.role == "assistant")
+        {
+            message.cancelled = true;
+            session.last_updated = chrono::Utc::now().to_rfc3339();
+            self.save_session(&session)?;
+        }
+
         Ok(())
     }
 
@@ -259,9 +377,7 @@ impl ConversationStorage {
         // Calculate total storage size
         let mut total_size = 0;
         for session in &sessions {
-            let session_file = self
-                .storage_dir
-                .join(format!("{}.json", session.session_id));
+            let session_file = self.session_file(&session.session_id);
             if let Ok(metadata) = session_file.metadata() {
                 total_size += metadata.len();
             }
@@ -289,15 +405,13 @@ impl ConversationStorage {
         let mut deleted_count = 0;
 
         for session in sessions.iter().take(to_delete) {
-            let session_file = self
-                .storage_dir
-                .join(format!("{}.json", session.session_id));
+            let session_file = self.session_file(&session.session_id);
             if session_file.exists() {
                 fs::remove_file(&session_file).map_err(|e| AppError::StorageError {
                     message: format!("Failed to delete old session file: {}", e),
                 })?;
                 deleted_count += 1;
-                println!("Deleted old session: {}", session.session_id);
+                tracing::info!("Deleted old session: {}", session.session_id);
             }
         }
 
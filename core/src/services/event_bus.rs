@@ -9,7 +9,7 @@ use crate::traits::{Event, EventBus, EventError, EventListener, EventType};
 type ListenerMap = HashMap<EventType, Vec<Box<dyn EventListener>>>;
 
 /// Event bus implementation
-#[allow(clippy::type_complexity, clippy::arc_with_non_send_sync)]
+#[allow(clippy::type_complexity)]
 pub struct EventBusImpl {
     listeners: Arc<Mutex<ListenerMap>>,
     event_queue: Arc<Mutex<Vec<Event>>>,
@@ -23,7 +23,6 @@ impl Default for EventBusImpl {
 
 impl EventBusImpl {
     /// Create a new event bus
-    #[allow(clippy::arc_with_non_send_sync)]
     pub fn new() -> Self {
         Self {
             listeners: Arc::new(Mutex::new(HashMap::new())),
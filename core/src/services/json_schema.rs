@@ -0,0 +1,245 @@
+//! JSON Schema validation
+//!
+//! Checks tool arguments against the schema the tool publishes, so tools
+//! only need to validate what a schema cannot express. Covers the keywords
+//! tool schemas use: `type`, `enum`, `const`, `minimum`, `maximum`,
+//! `exclusiveMinimum`, `exclusiveMaximum`, `minLength`, `maxLength`,
+//! `minItems`, `maxItems`, `items`, `required`, `properties` and
+//! `additionalProperties`. Other keywords are ignored.
+
+use serde_json::{Map, Value};
+
+use crate::traits::ValidationError;
+
+/// Check `instance` against `schema`, reporting the first violation found
+pub fn validate_against_schema(schema: &Value, instance: &Value) -> Result<(), ValidationError> {
+    Validator::default().validate(schema, instance)
+}
+
+/// Walks a schema and an instance together, tracking the JSON pointer
+#[derive(Default)]
+struct Validator {
+    path: Vec<String>,
+}
+
+impl Validator {
+    fn validate(&mut self, schema: &Value, instance: &Value) -> Result<(), ValidationError> {
+        let schema = match schema {
+            Value::Bool(true) => return Ok(()),
+            Value::Bool(false) => return Err(self.violation("false", "is not allowed")),
+            Value::Object(schema) => schema,
+            // Not a schema; nothing to check against
+            _ => return Ok(()),
+        };
+
+        self.check_type(schema, instance)?;
+        self.check_enum(schema, instance)?;
+        match instance {
+            Value::Number(_) => self.check_number(schema, instance)?,
+            Value::String(text) => self.check_string(schema, text)?,
+            Value::Array(items) => self.check_array(schema, items)?,
+            Value::Object(object) => self.check_object(schema, object)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn check_type(
+        &self,
+        schema: &Map<String, Value>,
+        instance: &Value,
+    ) -> Result<(), ValidationError> {
+        let expected: Vec<&str> = match schema.get("type") {
+            Some(Value::String(name)) => vec![name],
+            Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+            _ => return Ok(()),
+        };
+        if expected.iter().any(|name| has_type(instance, name)) {
+            return Ok(());
+        }
+        Err(self.violation(
+            "type",
+            format!(
+                "expected {}, found {}",
+                expected.join(" or "),
+                type_name(instance)
+            ),
+        ))
+    }
+
+    fn check_enum(
+        &self,
+        schema: &Map<String, Value>,
+        instance: &Value,
+    ) -> Result<(), ValidationError> {
+        if let Some(Value::Array(values)) = schema.get("enum") {
+            if !values.contains(instance) {
+                let values: Vec<String> = values.iter().map(Value::to_string).collect();
+                return Err(self.violation("enum", format!("must be one of {}", values.join(", "))));
+            }
+        }
+        if let Some(value) = schema.get("const") {
+            if value != instance {
+                return Err(self.violation("const", format!("must be {}", value)));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_number(
+        &self,
+        schema: &Map<String, Value>,
+        instance: &Value,
+    ) -> Result<(), ValidationError> {
+        let Some(number) = instance.as_f64() else {
+            return Ok(());
+        };
+        let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+
+        if let Some(minimum) = bound("minimum").filter(|&minimum| number < minimum) {
+            return Err(self.violation("minimum", format!("must be at least {}", minimum)));
+        }
+        if let Some(maximum) = bound("maximum").filter(|&maximum| number > maximum) {
+            return Err(self.violation("maximum", format!("must be at most {}", maximum)));
+        }
+        if let Some(minimum) = bound("exclusiveMinimum").filter(|&minimum| number <= minimum) {
+            return Err(self.violation(
+                "exclusiveMinimum",
+                format!("must be greater than {}", minimum),
+            ));
+        }
+        if let Some(maximum) = bound("exclusiveMaximum").filter(|&maximum| number >= maximum) {
+            return Err(
+                self.violation("exclusiveMaximum", format!("must be less than {}", maximum))
+            );
+        }
+        Ok(())
+    }
+
+    fn check_string(&self, schema: &Map<String, Value>, text: &str) -> Result<(), ValidationError> {
+        // Lengths count characters, not bytes
+        let length = text.chars().count() as u64;
+        if let Some(min) = limit(schema, "minLength").filter(|&min| length < min) {
+            return Err(self.violation(
+                "minLength",
+                format!("must be at least {} characters long", min),
+            ));
+        }
+        if let Some(max) = limit(schema, "maxLength").filter(|&max| length > max) {
+            return Err(self.violation(
+                "maxLength",
+                format!("must be at most {} characters long", max),
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_array(
+        &mut self,
+        schema: &Map<String, Value>,
+        items: &[Value],
+    ) -> Result<(), ValidationError> {
+        let count = items.len() as u64;
+        if let Some(min) = limit(schema, "minItems").filter(|&min| count < min) {
+            return Err(self.violation("minItems", format!("must have at least {} items", min)));
+        }
+        if let Some(max) = limit(schema, "maxItems").filter(|&max| count > max) {
+            return Err(self.violation("maxItems", format!("must have at most {} items", max)));
+        }
+
+        if let Some(item_schema) = schema.get("items") {
+            for (index, item) in items.iter().enumerate() {
+                self.path.push(index.to_string());
+                let result = self.validate(item_schema, item);
+                self.path.pop();
+                result?;
+            }
+        }
+        Ok(())
+    }
+
+    fn check_object(
+        &mut self,
+        schema: &Map<String, Value>,
+        object: &Map<String, Value>,
+    ) -> Result<(), ValidationError> {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    self.path.push(name.to_string());
+                    let error = self.violation("required", "is required");
+                    self.path.pop();
+                    return Err(error);
+                }
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (name, value) in object {
+            let property_schema = match properties.and_then(|properties| properties.get(name)) {
+                Some(property_schema) => property_schema,
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        self.path.push(name.clone());
+                        let error =
+                            self.violation("additionalProperties", "is not an allowed property");
+                        self.path.pop();
+                        return Err(error);
+                    }
+                    Some(additional) => additional,
+                    None => continue,
+                },
+            };
+
+            self.path.push(name.clone());
+            let result = self.validate(property_schema, value);
+            self.path.pop();
+            result?;
+        }
+        Ok(())
+    }
+
+    /// Error for the value at the current path
+    fn violation(&self, keyword: &str, message: impl Into<String>) -> ValidationError {
+        ValidationError::SchemaViolation {
+            pointer: self
+                .path
+                .iter()
+                .map(|token| format!("/{}", token.replace('~', "~0").replace('/', "~1")))
+                .collect(),
+            keyword: keyword.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Non-negative integer keyword such as `minLength`
+fn limit(schema: &Map<String, Value>, keyword: &str) -> Option<u64> {
+    schema.get(keyword).and_then(Value::as_u64)
+}
+
+fn has_type(instance: &Value, name: &str) -> bool {
+    match name {
+        "null" => instance.is_null(),
+        "boolean" => instance.is_boolean(),
+        "object" => instance.is_object(),
+        "array" => instance.is_array(),
+        "string" => instance.is_string(),
+        "number" => instance.is_number(),
+        // Tools read integers with `as_i64`/`as_u64`, so `1.0` is not accepted
+        "integer" => instance.is_i64() || instance.is_u64(),
+        _ => false,
+    }
+}
+
+fn type_name(instance: &Value) -> &'static str {
+    match instance {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) if has_type(instance, "integer") => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
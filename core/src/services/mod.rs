@@ -1,14 +1,24 @@
 //! Core services implementation
 
+pub mod command_policy;
 pub mod conversation_storage;
 pub mod event_bus;
+pub mod json_schema;
+pub mod path_policy;
 pub mod process_manager;
+#[cfg(unix)]
+mod pty;
+#[cfg(target_os = "linux")]
+mod sandbox;
 pub mod settings_manager;
 pub mod validation;
 
 // Re-export all services
+pub use command_policy::*;
 pub use conversation_storage::*;
 pub use event_bus::*;
+pub use json_schema::*;
+pub use path_policy::*;
 pub use process_manager::*;
 pub use settings_manager::*;
 pub use validation::*;
@@ -0,0 +1,90 @@
+//! Path policy
+//!
+//! Checks directories handed to the server by an agent before anything is
+//! written into them. Paths are canonicalized, so `..` components and
+//! symlinks cannot be used to step outside the allowed roots.
+
+use std::path::{Path, PathBuf};
+
+use crate::models::SecuritySettings;
+use crate::traits::{ValidationError, ValidationRule};
+
+/// Existing directories, optionally confined to a set of roots
+#[derive(Debug, Clone, Default)]
+pub struct PathPolicy {
+    roots: Vec<PathBuf>,
+}
+
+impl PathPolicy {
+    /// Accept directories inside any of `roots`; no roots accepts every directory
+    pub fn new(roots: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self {
+            roots: roots.into_iter().collect(),
+        }
+    }
+
+    /// Policy with the roots from `security.allowed_roots`
+    pub fn from_settings(settings: &SecuritySettings) -> Self {
+        Self::new(settings.allowed_roots.iter().cloned())
+    }
+
+    /// Also accept directories inside `roots`
+    pub fn with_roots(mut self, roots: impl IntoIterator<Item = PathBuf>) -> Self {
+        self.roots.extend(roots);
+        self
+    }
+
+    /// Check that `path` is an existing directory inside the roots,
+    /// returning its canonical form
+    pub fn check_directory(&self, path: &Path) -> Result<PathBuf, ValidationError> {
+        let invalid = |message: String| ValidationError::InvalidInput { message };
+
+        if !path.is_absolute() {
+            return Err(invalid(format!(
+                "{} is not an absolute path",
+                path.display()
+            )));
+        }
+        let canonical = path.canonicalize().map_err(|e| {
+            invalid(match e.kind() {
+                std::io::ErrorKind::NotFound => format!("{} does not exist", path.display()),
+                _ => format!("Cannot resolve {}: {}", path.display(), e),
+            })
+        })?;
+        if !canonical.is_dir() {
+            return Err(invalid(format!("{} is not a directory", path.display())));
+        }
+
+        if self.roots.is_empty() {
+            return Ok(canonical);
+        }
+        // Roots are resolved on every check since they may be created or moved later
+        let inside = self
+            .roots
+            .iter()
+            .filter_map(|root| root.canonicalize().ok())
+            .any(|root| canonical.starts_with(root));
+        if !inside {
+            return Err(invalid(format!(
+                "{} is outside the allowed roots ({})",
+                canonical.display(),
+                self.roots
+                    .iter()
+                    .map(|root| root.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+        Ok(canonical)
+    }
+}
+
+impl ValidationRule for PathPolicy {
+    fn validate(&self, input: &str) -> Result<(), ValidationError> {
+        self.check_directory(Path::new(input)).map(|_| ())
+    }
+
+    fn rule_name(&self) -> &str {
+        "PathPolicy"
+    }
+}
@@ -1,18 +1,419 @@
 //! Process manager implementation
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
 use std::path::Path;
-use std::process::Stdio;
-use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant, SystemTime};
 
+use serde_json::json;
+use sysinfo::{Pid, ProcessRefreshKind, System};
+use tokio::sync::broadcast;
+
+use crate::models::AppSettings;
+#[cfg(target_os = "linux")]
+use crate::services::sandbox::Sandbox;
 use crate::traits::{
-    ProcessError, ProcessHandle, ProcessManager, ProcessOutput, ProcessResult, ProcessStatus,
+    Event, EventBus, EventType, OutputLine, OutputStream, OutputSubscription, ProcessError,
+    ProcessHandle, ProcessManager, ProcessOutput, ProcessResult, ProcessStats, ProcessStatus,
+    SpawnOptions, TerminationReason,
 };
 
+/// How often a waiting caller checks whether the process has exited
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How often the supervisor thread checks on its process
+const SUPERVISOR_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How often the resource usage of a running process is sampled
+const STATS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Time a terminated process group gets to exit before it is killed
+const TERMINATION_GRACE: Duration = Duration::from_secs(5);
+
+/// Lines a subscriber may fall behind before it starts missing some
+const SUBSCRIBER_CAPACITY: usize = 1024;
+
+/// Bounded buffer of the most recent output lines of a process
+struct OutputBuffer {
+    lines: VecDeque<OutputLine>,
+    bytes: usize,
+    capacity: usize,
+    /// Pipe readers still running; output is complete once this reaches zero
+    open_pipes: usize,
+    /// Dropped once all pipes close, which ends every subscription
+    sender: Option<broadcast::Sender<OutputLine>>,
+}
+
+impl OutputBuffer {
+    fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(SUBSCRIBER_CAPACITY);
+        Self {
+            lines: VecDeque::new(),
+            bytes: 0,
+            capacity,
+            open_pipes: 0,
+            sender: Some(sender),
+        }
+    }
+
+    /// Append a line, evicting the oldest ones to stay within capacity
+    fn push(&mut self, line: OutputLine) {
+        if let Some(sender) = &self.sender {
+            // No receivers is fine; the line is still buffered
+            let _ = sender.send(line.clone());
+        }
+
+        self.bytes += line.text.len();
+        self.lines.push_back(line);
+        while self.bytes > self.capacity {
+            match self.lines.pop_front() {
+                Some(evicted) => self.bytes -= evicted.text.len(),
+                None => break,
+            }
+        }
+    }
+
+    fn pipe_closed(&mut self) {
+        self.open_pipes -= 1;
+        if self.open_pipes == 0 {
+            self.sender = None;
+        }
+    }
+
+    fn subscribe(&self) -> OutputSubscription {
+        let receiver = match &self.sender {
+            Some(sender) => sender.subscribe(),
+            None => {
+                // Output already ended: hand out a receiver that is closed
+                let (sender, receiver) = broadcast::channel(1);
+                drop(sender);
+                receiver
+            }
+        };
+        OutputSubscription {
+            backlog: self.lines.iter().cloned().collect(),
+            receiver,
+        }
+    }
+
+    /// Buffered lines of one stream, each terminated by a newline
+    fn text(&self, stream: OutputStream) -> String {
+        let mut text = String::new();
+        for line in self.lines.iter().filter(|line| line.stream == stream) {
+            text.push_str(&line.text);
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// Limits enforced on every process; `None` disables a limit
+#[derive(Debug, Clone, Copy)]
+struct Limits {
+    /// Longest a process may run before its group is terminated
+    max_process_time: Option<Duration>,
+    /// Most memory, in bytes, a process tree may use before it is terminated
+    max_memory_usage: Option<u64>,
+}
+
+/// Usage of a process tree at one point in time
+struct TreeUsage {
+    cpu_usage: f32,
+    memory_bytes: u64,
+    process_count: usize,
+}
+
+/// Samples process trees through `sysinfo`
+///
+/// CPU usage is measured between two refreshes, so each supervisor keeps
+/// its own sampler for the lifetime of its process.
+struct ResourceSampler {
+    system: System,
+}
+
+impl ResourceSampler {
+    fn new() -> Self {
+        Self {
+            system: System::new(),
+        }
+    }
+
+    /// Sum the usage of `root` and all of its descendants
+    fn sample(&mut self, root: u32) -> TreeUsage {
+        self.system
+            .refresh_processes_specifics(ProcessRefreshKind::new().with_cpu().with_memory());
+        let processes = self.system.processes();
+        let root = Pid::from_u32(root);
+
+        let mut usage = TreeUsage {
+            cpu_usage: 0.0,
+            memory_bytes: 0,
+            process_count: 0,
+        };
+        // Threads are listed as processes too but share their process's usage
+        for process in processes.values().filter(|p| p.thread_kind().is_none()) {
+            let mut ancestor = Some(process.pid());
+            while let Some(pid) = ancestor {
+                if pid == root {
+                    usage.cpu_usage += process.cpu_usage();
+                    usage.memory_bytes += process.memory();
+                    usage.process_count += 1;
+                    break;
+                }
+                ancestor = processes.get(&pid).and_then(|p| p.parent());
+            }
+        }
+        usage
+    }
+}
+
+/// Process owned by the manager
+struct ManagedProcess {
+    handle: ProcessHandle,
+    child: Child,
+    /// Master side of the pseudo-terminal of a PTY process
+    terminal: Option<File>,
+    output: Arc<Mutex<OutputBuffer>>,
+    started_at: Instant,
+    termination: Option<TerminationReason>,
+    /// When the group was asked to terminate
+    terminating_since: Option<Instant>,
+    exit: Option<(ExitStatus, Duration)>,
+    /// Whether the exited child was waited on, freeing its pid and process group id
+    reaped: bool,
+    /// Latest resource usage; `runtime` is filled in when it is read
+    stats: ProcessStats,
+}
+
+impl ManagedProcess {
+    /// Check for exit without blocking
+    ///
+    /// On Unix the exited child is left unreaped, so its process group id
+    /// cannot be reused while the group may still be signalled; see `reap`.
+    fn poll_exit(&mut self) -> Result<(), ProcessError> {
+        if self.exit.is_none() {
+            if let Some(status) = self
+                .peek_exit()
+                .map_err(|e| ProcessError::ExecutionFailed {
+                    message: format!("Failed to check process status: {}", e),
+                })?
+            {
+                self.exit = Some((status, self.started_at.elapsed()));
+                self.handle.status = self.final_status(status);
+                self.stats.cpu_usage = 0.0;
+                self.stats.memory_bytes = 0;
+                self.stats.process_count = 0;
+            }
+        }
+        Ok(())
+    }
+
+    /// Exit status of the child if it has exited, without reaping it
+    #[cfg(unix)]
+    fn peek_exit(&mut self) -> std::io::Result<Option<ExitStatus>> {
+        use std::os::unix::process::ExitStatusExt;
+
+        // SAFETY: an all-zero siginfo_t is valid, and waitid only writes to it
+        let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+        // SAFETY: `info` outlives the call
+        let waited = unsafe {
+            libc::waitid(
+                libc::P_PID,
+                self.child.id() as libc::id_t,
+                &mut info,
+                libc::WEXITED | libc::WNOHANG | libc::WNOWAIT,
+            )
+        };
+        if waited != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        // SAFETY: waitid filled in a SIGCHLD siginfo_t, or left it zeroed
+        let (pid, status) = unsafe { (info.si_pid(), info.si_status()) };
+        if pid == 0 {
+            return Ok(None);
+        }
+        // Rebuild the raw wait status the way waitpid would report it
+        let raw = match info.si_code {
+            libc::CLD_EXITED => (status & 0xff) << 8,
+            libc::CLD_DUMPED => status | 0x80,
+            _ => status,
+        };
+        Ok(Some(ExitStatus::from_raw(raw)))
+    }
+
+    #[cfg(not(unix))]
+    fn peek_exit(&mut self) -> std::io::Result<Option<ExitStatus>> {
+        let status = self.child.try_wait()?;
+        self.reaped = status.is_some();
+        Ok(status)
+    }
+
+    /// Reap the exited child, after which its process group must no longer be signalled
+    fn reap(&mut self) {
+        if self.exit.is_some() && !self.reaped {
+            if let Err(e) = self.child.wait() {
+                tracing::warn!("Failed to reap process {}: {}", self.handle.id, e);
+            }
+            self.reaped = true;
+        }
+    }
+
+    fn final_status(&self, status: ExitStatus) -> ProcessStatus {
+        if self.termination.is_some() {
+            ProcessStatus::Killed
+        } else if status.success() {
+            ProcessStatus::Completed
+        } else {
+            ProcessStatus::Failed
+        }
+    }
+
+    /// Exited and all output has been read
+    fn is_complete(&self) -> bool {
+        self.exit.is_some() && self.output.lock().unwrap().open_pipes == 0
+    }
+
+    fn snapshot(&self) -> (String, String) {
+        let output = self.output.lock().unwrap();
+        (
+            output.text(OutputStream::Stdout),
+            output.text(OutputStream::Stderr),
+        )
+    }
+
+    /// Result of a complete process
+    fn result(&self) -> Option<ProcessResult> {
+        let (status, duration) = self.exit?;
+        let (stdout, stderr) = self.snapshot();
+        Some(ProcessResult {
+            status: self.handle.status.clone(),
+            exit_code: status.code().unwrap_or(-1),
+            termination_reason: self.termination,
+            stdout,
+            stderr,
+            duration,
+        })
+    }
+
+    fn stats(&self) -> ProcessStats {
+        ProcessStats {
+            runtime: match self.exit {
+                Some((_, duration)) => duration,
+                None => self.started_at.elapsed(),
+            },
+            ..self.stats.clone()
+        }
+    }
+
+    /// Store a usage sample, terminating the group if it is over the memory limit
+    fn record_usage(&mut self, usage: TreeUsage, limits: Limits) -> std::io::Result<()> {
+        if self.exit.is_some() {
+            // Sampled after the process exited; keep the final figures
+            return Ok(());
+        }
+        self.stats.cpu_usage = usage.cpu_usage;
+        self.stats.memory_bytes = usage.memory_bytes;
+        self.stats.peak_memory_bytes = self.stats.peak_memory_bytes.max(usage.memory_bytes);
+        self.stats.process_count = usage.process_count;
+
+        let over_memory = limits
+            .max_memory_usage
+            .is_some_and(|max| usage.memory_bytes > max);
+        if self.terminating_since.is_none() && over_memory {
+            tracing::warn!(
+                "Process {} exceeded the memory limit ({} bytes), terminating it",
+                self.handle.id,
+                usage.memory_bytes
+            );
+            self.terminate_group(TerminationReason::MemoryLimit)?;
+        }
+        Ok(())
+    }
+
+    /// Send a signal to the process and everything it started
+    ///
+    /// Every child leads its own process group, so grandchildren are reached
+    /// too; a group that is already gone is not an error.
+    #[cfg(unix)]
+    fn signal_group(&mut self, signal: libc::c_int) -> std::io::Result<()> {
+        if self.reaped {
+            // The group id may already belong to an unrelated process group
+            return Ok(());
+        }
+        let pgid = self.child.id() as libc::pid_t;
+        // SAFETY: kill has no memory-safety preconditions
+        if unsafe { libc::kill(-pgid, signal) } == 0 {
+            return Ok(());
+        }
+        match std::io::Error::last_os_error() {
+            e if e.raw_os_error() == Some(libc::ESRCH) => Ok(()),
+            e => Err(e),
+        }
+    }
+
+    /// Kill the process group right away
+    fn kill_group(&mut self) -> std::io::Result<()> {
+        #[cfg(unix)]
+        return self.signal_group(libc::SIGKILL);
+        #[cfg(not(unix))]
+        return match self.exit {
+            Some(_) => Ok(()),
+            None => self.child.kill(),
+        };
+    }
+
+    /// Ask the process group to exit; it is killed if it is still around after the grace period
+    fn terminate_group(&mut self, reason: TerminationReason) -> std::io::Result<()> {
+        self.termination.get_or_insert(reason);
+        self.terminating_since.get_or_insert_with(Instant::now);
+        #[cfg(unix)]
+        return self.signal_group(libc::SIGTERM);
+        #[cfg(not(unix))]
+        return self.kill_group();
+    }
+
+    /// Apply the time limit and escalate terminations that were ignored
+    fn enforce_limits(&mut self, limits: Limits) -> std::io::Result<()> {
+        let over_time = limits
+            .max_process_time
+            .is_some_and(|max| self.started_at.elapsed() > max);
+        if self.exit.is_none() && self.terminating_since.is_none() && over_time {
+            tracing::warn!(
+                "Process {} exceeded the time limit, terminating it",
+                self.handle.id
+            );
+            self.terminate_group(TerminationReason::Timeout)?;
+        }
+
+        // Past the grace period, either the group ignored SIGTERM or the process
+        // exited while leftover grandchildren keep its output open
+        let grace_started = match (self.exit, self.terminating_since) {
+            (_, Some(since)) => Some(since),
+            (Some((_, duration)), None) => Some(self.started_at + duration),
+            (None, None) => None,
+        };
+        if grace_started.is_some_and(|since| since.elapsed() > TERMINATION_GRACE) {
+            self.kill_group()?;
+        }
+        Ok(())
+    }
+}
+
+type ProcessMap = HashMap<String, ManagedProcess>;
+
 /// Process manager implementation
 pub struct ProcessManagerImpl {
-    processes: Arc<Mutex<HashMap<String, ProcessHandle>>>,
+    processes: Arc<Mutex<ProcessMap>>,
+    /// Bytes of output kept per process
+    output_buffer_size: usize,
+    limits: Limits,
+    /// Restrictions for spawned commands, when `sandbox_mode` is on
+    #[cfg(target_os = "linux")]
+    sandbox: Option<Sandbox>,
+    event_bus: Option<Arc<dyn EventBus>>,
 }
 
 impl Default for ProcessManagerImpl {
@@ -24,27 +425,260 @@ impl Default for ProcessManagerImpl {
 impl ProcessManagerImpl {
     /// Create a new process manager
     pub fn new() -> Self {
+        Self::with_settings(&AppSettings::default())
+    }
+
+    /// Create a process manager with limits taken from the settings
+    pub fn with_settings(settings: &AppSettings) -> Self {
+        let max_memory_usage = Some(settings.performance.max_memory_usage as u64)
+            .filter(|&max_memory_usage| max_memory_usage > 0);
         Self {
             processes: Arc::new(Mutex::new(HashMap::new())),
+            output_buffer_size: settings.performance.cache_size,
+            limits: Limits {
+                max_process_time: Some(settings.security.max_process_time)
+                    .filter(|max_process_time| !max_process_time.is_zero()),
+                max_memory_usage,
+            },
+            #[cfg(target_os = "linux")]
+            sandbox: settings
+                .security
+                .sandbox_mode
+                .then_some(Sandbox { max_memory_usage }),
+            event_bus: None,
         }
     }
 
+    /// Publish `ProcessSpawned` and `ProcessCompleted` events on the bus
+    pub fn with_event_bus(mut self, event_bus: Arc<dyn EventBus>) -> Self {
+        self.event_bus = Some(event_bus);
+        self
+    }
+
     /// Generate a unique process ID
     fn generate_process_id(&self) -> String {
         uuid::Uuid::new_v4().to_string()
     }
+
+    /// Describe why a command could not be started
+    fn spawn_error(&self, command: &str, error: std::io::Error) -> ProcessError {
+        #[cfg(target_os = "linux")]
+        if self.sandbox.is_some() {
+            return Sandbox::spawn_error(command, error);
+        }
+        ProcessError::ExecutionFailed {
+            message: format!("Failed to start {}: {}", command, error),
+        }
+    }
+
+    /// Attach `cmd` to a new pseudo-terminal, returning its master side
+    #[cfg(unix)]
+    fn attach_terminal(cmd: &mut Command) -> Result<File, ProcessError> {
+        super::pty::attach(cmd).map_err(|e| ProcessError::ExecutionFailed {
+            message: format!("Failed to open a pseudo-terminal: {}", e),
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn attach_terminal(_cmd: &mut Command) -> Result<File, ProcessError> {
+        Err(ProcessError::ExecutionFailed {
+            message: "PTY mode is only supported on Unix".to_string(),
+        })
+    }
+
+    /// Split a pipe into lines on a background thread
+    fn spawn_pipe_reader<R>(pipe: R, stream: OutputStream, output: Arc<Mutex<OutputBuffer>>)
+    where
+        R: Read + Send + 'static,
+    {
+        output.lock().unwrap().open_pipes += 1;
+        std::thread::spawn(move || {
+            let mut reader = BufReader::new(pipe);
+            let mut line = Vec::new();
+            loop {
+                line.clear();
+                match reader.read_until(b'\n', &mut line) {
+                    Ok(0) => break,
+                    Ok(_) => {
+                        if line.ends_with(b"\n") {
+                            line.pop();
+                            if line.ends_with(b"\r") {
+                                line.pop();
+                            }
+                        }
+                        output.lock().unwrap().push(OutputLine {
+                            stream,
+                            text: String::from_utf8_lossy(&line).into_owned(),
+                            timestamp: SystemTime::now(),
+                        });
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                    // A pseudo-terminal reports EIO once every process has closed it
+                    #[cfg(unix)]
+                    Err(e) if e.raw_os_error() == Some(libc::EIO) => break,
+                    Err(e) => {
+                        tracing::warn!("Failed to read process output: {}", e);
+                        break;
+                    }
+                }
+            }
+            output.lock().unwrap().pipe_closed();
+        });
+    }
+
+    /// Watch a process on a background thread until it completes
+    fn spawn_supervisor(&self, process_id: String) {
+        let processes = Arc::downgrade(&self.processes);
+        let event_bus = self.event_bus.clone();
+        let limits = self.limits;
+
+        std::thread::spawn(move || {
+            let result = Self::supervise(&processes, &process_id, limits);
+            if let (Some(event_bus), Some(result)) = (event_bus, result) {
+                Self::publish(
+                    &event_bus,
+                    EventType::ProcessCompleted,
+                    json!({
+                        "id": process_id,
+                        "status": format!("{:?}", result.status),
+                        "exit_code": result.exit_code,
+                        "termination_reason": result.termination_reason.map(|r| format!("{:?}", r)),
+                        "duration_ms": result.duration.as_millis() as u64,
+                    }),
+                );
+            }
+        });
+    }
+
+    /// Poll until the process completes, sampling its resource usage and
+    /// enforcing limits meanwhile; `None` if the manager or process went away
+    fn supervise(
+        processes: &Weak<Mutex<ProcessMap>>,
+        process_id: &str,
+        limits: Limits,
+    ) -> Option<ProcessResult> {
+        let mut sampler = ResourceSampler::new();
+        let mut next_sample = Instant::now();
+        loop {
+            let sample_pid = {
+                let processes = processes.upgrade()?;
+                let mut processes = processes.lock().unwrap();
+                let process = processes.get_mut(process_id)?;
+                if let Err(e) = process.poll_exit() {
+                    tracing::warn!("Stopped supervising process {}: {}", process_id, e);
+                    return None;
+                }
+                if process.is_complete() {
+                    process.reap();
+                    return process.result();
+                }
+                if let Err(e) = process.enforce_limits(limits) {
+                    tracing::warn!("Failed to stop process {}: {}", process_id, e);
+                }
+                let due = process.exit.is_none() && Instant::now() >= next_sample;
+                due.then(|| process.child.id())
+            };
+
+            // Sampling reads all of /proc, so it happens without the lock held
+            if let Some(pid) = sample_pid {
+                let usage = sampler.sample(pid);
+                next_sample = Instant::now() + STATS_INTERVAL;
+
+                let processes = processes.upgrade()?;
+                let mut processes = processes.lock().unwrap();
+                let process = processes.get_mut(process_id)?;
+                if let Err(e) = process.record_usage(usage, limits) {
+                    tracing::warn!("Failed to stop process {}: {}", process_id, e);
+                }
+            }
+            std::thread::sleep(SUPERVISOR_POLL_INTERVAL);
+        }
+    }
+
+    fn publish(event_bus: &Arc<dyn EventBus>, event_type: EventType, data: serde_json::Value) {
+        let event = Event {
+            event_type,
+            data,
+            timestamp: SystemTime::now(),
+            source: "process_manager".to_string(),
+        };
+        if let Err(e) = event_bus.publish(event) {
+            tracing::warn!("Failed to publish process event: {}", e);
+        }
+    }
+
+    /// Run `f` on a tracked process
+    fn with_process<T>(
+        &self,
+        handle: &ProcessHandle,
+        f: impl FnOnce(&mut ManagedProcess) -> Result<T, ProcessError>,
+    ) -> Result<T, ProcessError> {
+        let mut processes = self.processes.lock().unwrap();
+        let process =
+            processes
+                .get_mut(&handle.id)
+                .ok_or_else(|| ProcessError::ProcessNotFound {
+                    id: handle.id.clone(),
+                })?;
+        f(process)
+    }
 }
 
 impl ProcessManager for ProcessManagerImpl {
-    fn spawn_process(
+    fn spawn_process_with_options(
         &self,
         command: &str,
         args: &[String],
         cwd: &Path,
+        options: &SpawnOptions,
     ) -> Result<ProcessHandle, ProcessError> {
         let process_id = self.generate_process_id();
 
-        // Create the process handle
+        // Spawn the actual process
+        let mut cmd = Command::new(command);
+        cmd.args(args);
+        cmd.current_dir(cwd);
+        let terminal = if options.pty {
+            // The process leads a new session, and with it a new process group
+            Some(Self::attach_terminal(&mut cmd)?)
+        } else {
+            cmd.stdin(Stdio::null());
+            cmd.stdout(Stdio::piped());
+            cmd.stderr(Stdio::piped());
+            #[cfg(unix)]
+            {
+                use std::os::unix::process::CommandExt;
+                // Lead a new process group so the whole tree can be signalled at once
+                cmd.process_group(0);
+            }
+            None
+        };
+        #[cfg(target_os = "linux")]
+        if let Some(sandbox) = &self.sandbox {
+            sandbox.apply(&mut cmd, cwd)?;
+        }
+
+        let started_at = Instant::now();
+        let mut child = cmd.spawn().map_err(|e| self.spawn_error(command, e))?;
+        // Close our copies of the terminal so reading ends once the process is done
+        drop(cmd);
+
+        let output = Arc::new(Mutex::new(OutputBuffer::new(self.output_buffer_size)));
+        if let Some(terminal) = &terminal {
+            let reader = terminal
+                .try_clone()
+                .map_err(|e| ProcessError::ExecutionFailed {
+                    message: format!("Failed to read from the pseudo-terminal: {}", e),
+                })?;
+            Self::spawn_pipe_reader(reader, OutputStream::Stdout, output.clone());
+        }
+        if let Some(stdout) = child.stdout.take() {
+            Self::spawn_pipe_reader(stdout, OutputStream::Stdout, output.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            Self::spawn_pipe_reader(stderr, OutputStream::Stderr, output.clone());
+        }
+
         let handle = ProcessHandle {
             id: process_id.clone(),
             command: command.to_string(),
@@ -53,82 +687,168 @@ impl ProcessManager for ProcessManagerImpl {
             status: ProcessStatus::Running,
         };
 
-        // Store the handle
         {
             let mut processes = self.processes.lock().unwrap();
-            processes.insert(process_id.clone(), handle.clone());
+            processes.insert(
+                process_id.clone(),
+                ManagedProcess {
+                    handle: handle.clone(),
+                    child,
+                    terminal,
+                    output,
+                    started_at,
+                    termination: None,
+                    terminating_since: None,
+                    exit: None,
+                    reaped: false,
+                    stats: ProcessStats::default(),
+                },
+            );
         }
 
-        // Spawn the actual process
-        let mut cmd = std::process::Command::new(command);
-        cmd.args(args);
-        cmd.current_dir(cwd);
-        cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::piped());
-
-        match cmd.spawn() {
-            Ok(_child) => {
-                // Update the handle with the actual process
-                let mut processes = self.processes.lock().unwrap();
-                if let Some(handle) = processes.get_mut(&process_id) {
-                    handle.status = ProcessStatus::Running;
-                }
-                Ok(handle)
+        if let Some(event_bus) = &self.event_bus {
+            Self::publish(
+                event_bus,
+                EventType::ProcessSpawned,
+                json!({
+                    "id": process_id,
+                    "command": command,
+                    "args": args,
+                    "cwd": cwd,
+                }),
+            );
+        }
+        self.spawn_supervisor(process_id);
+
+        Ok(handle)
+    }
+
+    fn write_stdin(&self, handle: &ProcessHandle, data: &[u8]) -> Result<(), ProcessError> {
+        self.with_process(handle, |process| {
+            process.poll_exit()?;
+            if process.exit.is_some() {
+                return Err(ProcessError::ExecutionFailed {
+                    message: format!("Process {} has already exited", handle.id),
+                });
             }
-            Err(e) => {
-                // Remove the handle if process creation failed
-                let mut processes = self.processes.lock().unwrap();
-                processes.remove(&process_id);
-                Err(ProcessError::ExecutionFailed {
-                    message: e.to_string(),
+            let terminal =
+                process
+                    .terminal
+                    .as_mut()
+                    .ok_or_else(|| ProcessError::ExecutionFailed {
+                        message: "Only processes spawned in PTY mode accept input".to_string(),
+                    })?;
+            terminal
+                .write_all(data)
+                .and_then(|_| terminal.flush())
+                .map_err(|e| ProcessError::ExecutionFailed {
+                    message: format!("Failed to write to process: {}", e),
                 })
-            }
-        }
+        })
     }
 
     fn kill_process(&self, handle: &ProcessHandle) -> Result<(), ProcessError> {
-        let mut processes = self.processes.lock().unwrap();
-        if let Some(process_handle) = processes.get_mut(&handle.id) {
-            process_handle.status = ProcessStatus::Killed;
-            Ok(())
-        } else {
-            Err(ProcessError::ProcessNotFound {
-                id: handle.id.clone(),
-            })
-        }
+        self.with_process(handle, |process| {
+            process.poll_exit()?;
+            if process.is_complete() {
+                // Already finished; nothing to kill
+                return Ok(());
+            }
+
+            // Also reaps grandchildren still holding the output of an exited process
+            if process.exit.is_none() {
+                process
+                    .termination
+                    .get_or_insert(TerminationReason::Requested);
+            }
+            process
+                .kill_group()
+                .map_err(|e| ProcessError::ExecutionFailed {
+                    message: format!("Failed to kill process: {}", e),
+                })
+        })
     }
 
     fn wait_for_process(&self, handle: &ProcessHandle) -> Result<ProcessResult, ProcessError> {
-        // This is a simplified implementation
-        // In a real implementation, you would wait for the actual process
-        let start_time = Instant::now();
-
-        // Simulate process execution
-        std::thread::sleep(Duration::from_millis(100));
-
-        let result = ProcessResult {
-            exit_code: 0,
-            stdout: "Process completed successfully".to_string(),
-            stderr: String::new(),
-            duration: start_time.elapsed(),
-        };
-
-        // Update the handle status
-        let mut processes = self.processes.lock().unwrap();
-        if let Some(process_handle) = processes.get_mut(&handle.id) {
-            process_handle.status = ProcessStatus::Completed;
+        // Poll rather than block so the lock is free for kill_process meanwhile
+        loop {
+            if let Some(result) = self.try_wait_process(handle)? {
+                return Ok(result);
+            }
+            std::thread::sleep(WAIT_POLL_INTERVAL);
         }
+    }
 
-        Ok(result)
+    fn try_wait_process(
+        &self,
+        handle: &ProcessHandle,
+    ) -> Result<Option<ProcessResult>, ProcessError> {
+        self.with_process(handle, |process| {
+            process.poll_exit()?;
+            Ok(process.is_complete().then(|| process.result()).flatten())
+        })
     }
 
-    fn get_process_output(&self, _handle: &ProcessHandle) -> Result<ProcessOutput, ProcessError> {
-        // This is a simplified implementation
-        // In a real implementation, you would read from the actual process
-        Ok(ProcessOutput {
-            stdout: "Process output".to_string(),
-            stderr: String::new(),
-            is_complete: true,
+    fn get_process_output(&self, handle: &ProcessHandle) -> Result<ProcessOutput, ProcessError> {
+        self.with_process(handle, |process| {
+            process.poll_exit()?;
+            let (stdout, stderr) = process.snapshot();
+            Ok(ProcessOutput {
+                stdout,
+                stderr,
+                is_complete: process.is_complete(),
+            })
         })
     }
+
+    fn subscribe_output(&self, handle: &ProcessHandle) -> Result<OutputSubscription, ProcessError> {
+        self.with_process(handle, |process| {
+            Ok(process.output.lock().unwrap().subscribe())
+        })
+    }
+
+    fn process_stats(&self, handle: &ProcessHandle) -> Result<ProcessStats, ProcessError> {
+        self.with_process(handle, |process| Ok(process.stats()))
+    }
+
+    fn remove_process(&self, handle: &ProcessHandle) -> Result<(), ProcessError> {
+        let mut process = self
+            .processes
+            .lock()
+            .unwrap()
+            .remove(&handle.id)
+            .ok_or_else(|| ProcessError::ProcessNotFound {
+                id: handle.id.clone(),
+            })?;
+
+        // Reap the child whatever happens, so removing it never leaves a zombie
+        let killed = match process.poll_exit() {
+            Ok(()) if process.is_complete() => Ok(()),
+            _ => process.kill_group(),
+        };
+        if process.exit.is_none() {
+            let _ = process.child.wait();
+            process.reaped = true;
+        }
+        process.reap();
+        killed.map_err(|e| ProcessError::ExecutionFailed {
+            message: format!("Failed to kill process: {}", e),
+        })
+    }
+}
+
+impl Drop for ProcessManagerImpl {
+    fn drop(&mut self) {
+        // Don't leave children running once nobody can wait for them
+        if let Ok(mut processes) = self.processes.lock() {
+            for process in processes.values_mut() {
+                if !process.is_complete() {
+                    let _ = process.kill_group();
+                }
+                if !process.reaped {
+                    let _ = process.child.wait();
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,116 @@
+//! Linux sandbox for spawned commands
+//!
+//! A sandboxed command runs in fresh user and network namespaces, so it has
+//! no network beyond a loopback interface that is down, and under a Landlock
+//! ruleset that leaves only its working directory (plus `/dev/null` and
+//! `/dev/tty`) writable. Its data segment is capped with `setrlimit` unless the
+//! memory limit is disabled. Nothing falls back
+//! to running unconfined: when the kernel lacks one of these features the
+//! command is not started.
+
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::process::Command;
+
+use landlock::{
+    path_beneath_rules, Access, AccessFs, CompatLevel, Compatible, RulesetAttr, RulesetCreated,
+    RulesetCreatedAttr, RulesetError, RulesetStatus, ABI,
+};
+
+use crate::traits::ProcessError;
+
+/// Oldest Landlock ABI that can confine writes without breaking renames
+const REQUIRED_ABI: ABI = ABI::V2;
+
+/// Newest Landlock ABI whose extra rights are handled when available
+const PREFERRED_ABI: ABI = ABI::V5;
+
+/// Limits applied to every sandboxed command
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Sandbox {
+    /// Cap on the data segment of each process, in bytes; `None` leaves it unlimited
+    pub max_memory_usage: Option<u64>,
+}
+
+impl Sandbox {
+    /// Confine `cmd` to `writable_dir` once it is spawned
+    ///
+    /// The ruleset is built here, in the parent, so that the child only has
+    /// to make system calls between `fork` and `exec`.
+    pub fn apply(&self, cmd: &mut Command, writable_dir: &Path) -> Result<(), ProcessError> {
+        let ruleset = Self::ruleset(writable_dir).map_err(|e| ProcessError::PermissionDenied {
+            message: format!(
+                "sandbox_mode is enabled but Landlock is unavailable ({}); \
+                 disable security.sandbox_mode to run commands unconfined",
+                e
+            ),
+        })?;
+
+        let limit = self.max_memory_usage.map(|limit| limit as libc::rlim_t);
+        let mut ruleset = Some(ruleset);
+        // SAFETY: the hook only makes system calls and allocates nothing
+        // unless Landlock fails, which is safe in the forked child.
+        unsafe {
+            cmd.pre_exec(move || {
+                if let Some(limit) = limit {
+                    let rlimit = libc::rlimit {
+                        rlim_cur: limit,
+                        rlim_max: limit,
+                    };
+                    if libc::setrlimit(libc::RLIMIT_DATA, &rlimit) != 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                }
+                if libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                if let Some(ruleset) = ruleset.take() {
+                    match ruleset.restrict_self() {
+                        Ok(status) if status.ruleset != RulesetStatus::NotEnforced => {}
+                        _ => return Err(io::Error::from_raw_os_error(libc::EPERM)),
+                    }
+                }
+                Ok(())
+            });
+        }
+        Ok(())
+    }
+
+    /// Landlock ruleset allowing reads everywhere and writes in `writable_dir`
+    fn ruleset(writable_dir: &Path) -> Result<RulesetCreated, RulesetError> {
+        landlock::Ruleset::default()
+            .set_compatibility(CompatLevel::HardRequirement)
+            .handle_access(AccessFs::from_all(REQUIRED_ABI))?
+            .set_compatibility(CompatLevel::BestEffort)
+            .handle_access(AccessFs::from_all(PREFERRED_ABI))?
+            .create()?
+            .add_rules(path_beneath_rules(
+                ["/"],
+                AccessFs::from_read(PREFERRED_ABI),
+            ))?
+            .add_rules(path_beneath_rules(
+                [writable_dir, Path::new("/dev/null"), Path::new("/dev/tty")],
+                AccessFs::from_all(PREFERRED_ABI),
+            ))
+    }
+
+    /// Describe a spawn failure, blaming the sandbox when it is the likely cause
+    pub fn spawn_error(command: &str, error: io::Error) -> ProcessError {
+        match error.raw_os_error() {
+            Some(libc::EPERM | libc::EINVAL | libc::ENOSPC | libc::ENOSYS) => {
+                ProcessError::PermissionDenied {
+                    message: format!(
+                        "Cannot sandbox {}: the kernel refused to isolate it ({}); \
+                         unprivileged user namespaces may be disabled. \
+                         Disable security.sandbox_mode to run commands unconfined",
+                        command, error
+                    ),
+                }
+            }
+            _ => ProcessError::ExecutionFailed {
+                message: format!("Failed to start {}: {}", command, error),
+            },
+        }
+    }
+}
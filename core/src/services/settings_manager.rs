@@ -1,8 +1,8 @@
 //! Settings manager implementation
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::models::AppSettings;
+use crate::models::{AppSettings, ProjectSettings};
 use crate::traits::{SettingsError, SettingsManager};
 
 /// Settings manager implementation
@@ -20,13 +20,26 @@ impl Default for SettingsManagerImpl {
 impl SettingsManagerImpl {
     /// Create a new settings manager
     pub fn new() -> Self {
-        let settings_path = Self::get_settings_path();
+        Self::with_path(Self::get_settings_path())
+    }
+
+    /// Create a settings manager that keeps its settings at `settings_path`
+    pub fn with_path(settings_path: PathBuf) -> Self {
         Self {
             settings: AppSettings::default(),
             settings_path,
         }
     }
 
+    /// Change the settings and save them
+    pub fn update_settings(
+        &mut self,
+        update: impl FnOnce(&mut AppSettings),
+    ) -> Result<(), SettingsError> {
+        update(&mut self.settings);
+        self.save_settings()
+    }
+
     /// Get the settings file path
     fn get_settings_path() -> PathBuf {
         dirs::config_dir()
@@ -34,9 +47,46 @@ impl SettingsManagerImpl {
             .join("ifm-ruta")
             .join("settings.toml")
     }
+
+    /// Get the project settings file path
+    fn get_project_settings_path(project_directory: &Path) -> PathBuf {
+        project_directory.join(".ifm-ruta").join("project.toml")
+    }
+
+    /// Load a project's settings, falling back to defaults if it has none
+    pub fn load_project_settings(
+        project_directory: &Path,
+    ) -> Result<ProjectSettings, SettingsError> {
+        let settings_path = Self::get_project_settings_path(project_directory);
+        if !settings_path.exists() {
+            return Ok(ProjectSettings::default());
+        }
+
+        let content = std::fs::read_to_string(&settings_path)?;
+        Ok(toml::from_str(&content)?)
+    }
+
+    /// Save a project's settings under its `.ifm-ruta` directory
+    pub fn save_project_settings(
+        project_directory: &Path,
+        settings: &ProjectSettings,
+    ) -> Result<(), SettingsError> {
+        let settings_path = Self::get_project_settings_path(project_directory);
+        if let Some(parent) = settings_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let content = toml::to_string_pretty(settings)?;
+        std::fs::write(&settings_path, content)?;
+        Ok(())
+    }
 }
 
 impl SettingsManager for SettingsManagerImpl {
+    fn get_settings(&self) -> AppSettings {
+        self.settings.clone()
+    }
+
     fn load_settings(&mut self) -> Result<(), SettingsError> {
         if self.settings_path.exists() {
             let content = std::fs::read_to_string(&self.settings_path)?;
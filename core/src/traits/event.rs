@@ -5,7 +5,7 @@ use std::result::Result;
 use std::time::SystemTime;
 
 /// Event bus interface
-pub trait EventBus {
+pub trait EventBus: Send + Sync {
     /// Subscribe to events of a specific type
     fn subscribe(&mut self, event_type: EventType, listener: Box<dyn EventListener>);
 
@@ -17,7 +17,7 @@ pub trait EventBus {
 }
 
 /// Event listener interface
-pub trait EventListener {
+pub trait EventListener: Send + Sync {
     /// Handle an event
     fn handle_event(&self, event: &Event) -> Result<(), EventError>;
 
@@ -2,26 +2,71 @@
 
 use std::path::Path;
 use std::result::Result;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
+
+use tokio::sync::broadcast;
 
 /// Process management interface
-pub trait ProcessManager {
-    /// Spawn a new process
+pub trait ProcessManager: Send + Sync {
+    /// Spawn a new process with its output on pipes
     fn spawn_process(
         &self,
         command: &str,
         args: &[String],
         cwd: &Path,
+    ) -> Result<ProcessHandle, ProcessError> {
+        self.spawn_process_with_options(command, args, cwd, &SpawnOptions::default())
+    }
+
+    /// Spawn a new process, choosing how it is attached
+    fn spawn_process_with_options(
+        &self,
+        command: &str,
+        args: &[String],
+        cwd: &Path,
+        options: &SpawnOptions,
     ) -> Result<ProcessHandle, ProcessError>;
 
+    /// Write to the stdin of a process spawned with `SpawnOptions::pty`
+    fn write_stdin(&self, handle: &ProcessHandle, data: &[u8]) -> Result<(), ProcessError>;
+
     /// Kill a running process
     fn kill_process(&self, handle: &ProcessHandle) -> Result<(), ProcessError>;
 
     /// Wait for a process to complete
     fn wait_for_process(&self, handle: &ProcessHandle) -> Result<ProcessResult, ProcessError>;
 
+    /// Result of a process if it has completed, without blocking
+    fn try_wait_process(
+        &self,
+        handle: &ProcessHandle,
+    ) -> Result<Option<ProcessResult>, ProcessError>;
+
     /// Get process output (stdout/stderr)
     fn get_process_output(&self, handle: &ProcessHandle) -> Result<ProcessOutput, ProcessError>;
+
+    /// Follow a process's output line by line
+    fn subscribe_output(&self, handle: &ProcessHandle) -> Result<OutputSubscription, ProcessError>;
+
+    /// Latest resource usage of a process and its descendants
+    fn process_stats(&self, handle: &ProcessHandle) -> Result<ProcessStats, ProcessError>;
+
+    /// Stop tracking a process and free its output, killing it if it still runs
+    ///
+    /// The handle is unknown to the manager afterwards.
+    fn remove_process(&self, handle: &ProcessHandle) -> Result<(), ProcessError>;
+}
+
+/// How a process is attached to the manager
+#[derive(Debug, Clone, Default)]
+pub struct SpawnOptions {
+    /// Run the process on a pseudo-terminal instead of pipes
+    ///
+    /// Commands then keep their colors and interactive behavior. Output
+    /// arrives as a single `Stdout` stream that may contain ANSI escape
+    /// sequences (see `utils::ansi`), and input can be sent with
+    /// `ProcessManager::write_stdin`. Only supported on Unix.
+    pub pty: bool,
 }
 
 /// Process handle for tracking spawned processes
@@ -46,12 +91,47 @@ pub enum ProcessStatus {
 /// Process execution result
 #[derive(Debug, Clone)]
 pub struct ProcessResult {
+    /// `Completed` on a zero exit code, `Failed` otherwise, `Killed` if stopped
+    pub status: ProcessStatus,
+    /// Exit code, or -1 if the process was ended by a signal
     pub exit_code: i32,
+    /// Why the process was stopped, if it did not exit on its own
+    pub termination_reason: Option<TerminationReason>,
     pub stdout: String,
     pub stderr: String,
     pub duration: Duration,
 }
 
+/// Why a process was stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminationReason {
+    /// `kill_process` was called
+    Requested,
+    /// The process ran longer than `SecuritySettings.max_process_time`
+    Timeout,
+    /// The process tree used more than `PerformanceSettings.max_memory_usage`
+    MemoryLimit,
+}
+
+/// Resource usage of a process tree, sampled periodically while it runs
+///
+/// Usage covers the process and every descendant still attached to it. Once
+/// the process exits, the current figures drop to zero while the peak and
+/// runtime are kept.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProcessStats {
+    /// Combined CPU usage, in percent of one core
+    pub cpu_usage: f32,
+    /// Combined resident set size, in bytes
+    pub memory_bytes: u64,
+    /// Highest combined resident set size seen so far, in bytes
+    pub peak_memory_bytes: u64,
+    /// Processes in the tree at the last sample
+    pub process_count: usize,
+    /// Time since the process started, up to its exit
+    pub runtime: Duration,
+}
+
 /// Process output
 #[derive(Debug, Clone)]
 pub struct ProcessOutput {
@@ -60,6 +140,31 @@ pub struct ProcessOutput {
     pub is_complete: bool,
 }
 
+/// Stream a line of output was written to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Line of process output, without its line terminator
+#[derive(Debug, Clone)]
+pub struct OutputLine {
+    pub stream: OutputStream,
+    pub text: String,
+    pub timestamp: SystemTime,
+}
+
+/// Live view of a process's output
+///
+/// `backlog` holds the lines still buffered when the subscription started;
+/// later lines arrive on `receiver`, which closes once the process's output
+/// ends. The receiver works both with `recv().await` and `try_recv()`.
+pub struct OutputSubscription {
+    pub backlog: Vec<OutputLine>,
+    pub receiver: broadcast::Receiver<OutputLine>,
+}
+
 /// Process management error
 #[derive(Debug, thiserror::Error)]
 pub enum ProcessError {
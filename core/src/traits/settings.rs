@@ -2,8 +2,13 @@
 
 use std::result::Result;
 
+use crate::models::AppSettings;
+
 /// Settings management interface
 pub trait SettingsManager: Send + Sync {
+    /// Get a snapshot of the current settings
+    fn get_settings(&self) -> AppSettings;
+
     /// Load settings from storage
     fn load_settings(&mut self) -> Result<(), SettingsError>;
 
@@ -1,10 +1,24 @@
 //! Tool interface and related types
 
 use serde_json::Value;
+use std::path::PathBuf;
 use std::result::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::Notify;
+
+use crate::traits::{EventBus, ProcessManager, SettingsManager};
+
+/// Key of a tool result holding extra MCP content items (e.g. `image`)
+///
+/// The server removes this array from the result and returns its items
+/// after the text item carrying the rest of the result.
+pub const EXTRA_CONTENT_KEY: &str = "_content";
 
 /// Core tool interface for MCP tools
-pub trait Tool {
+#[async_trait::async_trait]
+pub trait Tool: Send + Sync {
     /// Get the tool name
     fn name(&self) -> &str;
 
@@ -15,10 +29,131 @@ pub trait Tool {
     fn input_schema(&self) -> Value;
 
     /// Execute the tool with given input
-    fn execute(&self, input: Value) -> Result<Value, ToolError>;
+    ///
+    /// When the client cancels the call, tools should notice through
+    /// `context.cancellation` and return `ToolError::Cancelled`; the server
+    /// aborts calls that are still running after a short grace period.
+    async fn execute(&self, input: Value, context: &ToolContext) -> Result<Value, ToolError>;
+
+    /// Check what `input_schema` cannot express
+    ///
+    /// The server validates the input against `input_schema` before calling
+    /// this, so the default accepts everything. Tools may normalize fields in
+    /// place; `execute` receives the input as this leaves it.
+    fn validate_input(
+        &self,
+        input: &mut Value,
+        context: &ToolContext,
+    ) -> Result<(), ValidationError> {
+        let _ = (input, context);
+        Ok(())
+    }
+}
+
+/// Everything a tool call can use besides its input
+#[derive(Clone)]
+pub struct ToolContext {
+    /// JSON-RPC id of the `tools/call` request
+    pub request_id: Value,
+    /// Identifies the client connection the call came from
+    pub session_id: String,
+    /// Filesystem roots the client exposed, empty if it has none
+    pub roots: Vec<PathBuf>,
+    pub progress: ProgressReporter,
+    pub cancellation: CancellationToken,
+    pub settings: Arc<dyn SettingsManager>,
+    pub process_manager: Arc<dyn ProcessManager>,
+    pub event_bus: Arc<dyn EventBus>,
+}
+
+impl ToolContext {
+    /// Context for a call outside any request, with no progress reporting
+    pub fn new(
+        settings: Arc<dyn SettingsManager>,
+        process_manager: Arc<dyn ProcessManager>,
+        event_bus: Arc<dyn EventBus>,
+    ) -> Self {
+        Self {
+            request_id: Value::Null,
+            session_id: String::new(),
+            roots: Vec::new(),
+            progress: ProgressReporter::default(),
+            cancellation: CancellationToken::new(),
+            settings,
+            process_manager,
+            event_bus,
+        }
+    }
+}
+
+/// Receives progress updates from a running tool
+pub trait ProgressSink: Send + Sync {
+    /// Handle an update; `progress` must increase with every call
+    fn report(&self, progress: f64, total: Option<f64>, message: Option<&str>);
+}
+
+/// Reports progress of a tool call, if the caller asked for it
+#[derive(Clone, Default)]
+pub struct ProgressReporter {
+    sink: Option<Arc<dyn ProgressSink>>,
+}
+
+impl ProgressReporter {
+    /// Reporter that passes updates to `sink`
+    pub fn new(sink: Arc<dyn ProgressSink>) -> Self {
+        Self { sink: Some(sink) }
+    }
+
+    /// Whether anyone receives the updates
+    pub fn is_enabled(&self) -> bool {
+        self.sink.is_some()
+    }
+
+    /// Report progress; does nothing if the caller did not ask for it
+    pub fn report(&self, progress: f64, total: Option<f64>, message: Option<&str>) {
+        if let Some(sink) = &self.sink {
+            sink.report(progress, total, message);
+        }
+    }
+}
+
+/// Cancellation flag shared between the server and a running tool
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    notify: Arc<Notify>,
+}
+
+impl CancellationToken {
+    /// Create a new, not yet cancelled token
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
 
-    /// Validate input against the tool's schema
-    fn validate_input(&self, input: &Value) -> Result<(), ValidationError>;
+    /// Check whether cancellation was requested
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Wait until cancellation is requested
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            // Register before checking the flag so a cancel in between is not missed
+            notified.as_mut().enable();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
 }
 
 /// Tool execution error
@@ -33,6 +168,9 @@ pub enum ToolError {
     #[error("Timeout error: tool execution timed out")]
     TimeoutError,
 
+    #[error("Cancelled: tool execution was cancelled")]
+    Cancelled,
+
     #[error("Permission error: {message}")]
     PermissionError { message: String },
 
@@ -54,4 +192,14 @@ pub enum ValidationError {
 
     #[error("Value out of range: {field}")]
     OutOfRange { field: String },
+
+    /// Value that does not satisfy a JSON Schema keyword
+    #[error("Invalid value at `{pointer}`: {message}")]
+    SchemaViolation {
+        /// JSON pointer to the offending value, e.g. `/options/0/label`
+        pointer: String,
+        /// Schema keyword that failed, e.g. `required` or `minimum`
+        keyword: String,
+        message: String,
+    },
 }
@@ -0,0 +1,194 @@
+//! ANSI escape sequence parsing
+//!
+//! Turns terminal output, as produced by commands run in PTY mode, into
+//! spans of plain text with the style they were printed in. Only SGR
+//! sequences (colors and text attributes) affect the result; cursor
+//! movement, screen clearing, window titles and similar sequences are
+//! dropped.
+
+/// Terminal color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnsiColor {
+    /// Entry of the 256-color palette; 0-7 are the standard colors and 8-15
+    /// their bright variants
+    Indexed(u8),
+    /// 24-bit color
+    Rgb(u8, u8, u8),
+}
+
+/// Style of a piece of terminal output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AnsiStyle {
+    /// Foreground color, `None` for the terminal default
+    pub foreground: Option<AnsiColor>,
+    /// Background color, `None` for the terminal default
+    pub background: Option<AnsiColor>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+    /// Foreground and background are swapped
+    pub inverse: bool,
+}
+
+/// Text printed in a single style
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyledSpan {
+    pub text: String,
+    pub style: AnsiStyle,
+}
+
+/// Parser for terminal output, fed one line at a time
+///
+/// The current style carries over from one line to the next, as it does in
+/// a terminal.
+#[derive(Debug, Default)]
+pub struct AnsiParser {
+    style: AnsiStyle,
+}
+
+impl AnsiParser {
+    /// Create a parser starting with the default style
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Split a line, without its terminator, into styled spans
+    ///
+    /// A carriage return followed by more text discards what came before it
+    /// on the line, so progress bars only keep their final state.
+    pub fn parse_line(&mut self, line: &str) -> Vec<StyledSpan> {
+        let mut spans = Vec::new();
+        let mut text = String::new();
+        let mut carriage_return = false;
+
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\x1b' => match chars.next() {
+                    // Control sequence: parameters up to a final byte in '@'..='~'
+                    Some('[') => {
+                        let mut params = String::new();
+                        let mut final_byte = None;
+                        for c in chars.by_ref() {
+                            if ('@'..='~').contains(&c) {
+                                final_byte = Some(c);
+                                break;
+                            }
+                            params.push(c);
+                        }
+                        if final_byte == Some('m') {
+                            self.flush(&mut spans, &mut text);
+                            self.apply_sgr(&params);
+                        }
+                    }
+                    // Operating system command, ended by BEL or ESC \
+                    Some(']') => {
+                        while let Some(c) = chars.next() {
+                            if c == '\x07' {
+                                break;
+                            }
+                            if c == '\x1b' {
+                                chars.next_if_eq(&'\\');
+                                break;
+                            }
+                        }
+                    }
+                    // Character set selection takes one more character
+                    Some('(' | ')' | '*' | '+') => {
+                        chars.next();
+                    }
+                    _ => {}
+                },
+                '\r' => carriage_return = true,
+                c if c == '\t' || !c.is_control() => {
+                    if carriage_return {
+                        spans.clear();
+                        text.clear();
+                        carriage_return = false;
+                    }
+                    text.push(c);
+                }
+                _ => {}
+            }
+        }
+        self.flush(&mut spans, &mut text);
+        spans
+    }
+
+    /// End the current span, if it has any text
+    fn flush(&self, spans: &mut Vec<StyledSpan>, text: &mut String) {
+        if !text.is_empty() {
+            spans.push(StyledSpan {
+                text: std::mem::take(text),
+                style: self.style,
+            });
+        }
+    }
+
+    /// Apply a Select Graphic Rendition sequence
+    fn apply_sgr(&mut self, params: &str) {
+        // Empty parameters count as 0, except within colon-separated
+        // sub-parameters (`38:2::r:g:b`), where they are skipped
+        let mut codes = params.split(';').flat_map(|group| {
+            let parse = |code: &str| code.parse::<u16>().unwrap_or(0);
+            if group.contains(':') {
+                group
+                    .split(':')
+                    .filter(|code| !code.is_empty())
+                    .map(parse)
+                    .collect()
+            } else {
+                vec![parse(group)]
+            }
+        });
+
+        while let Some(code) = codes.next() {
+            match code {
+                0 => self.style = AnsiStyle::default(),
+                1 => self.style.bold = true,
+                2 => self.style.dim = true,
+                3 => self.style.italic = true,
+                4 => self.style.underline = true,
+                7 => self.style.inverse = true,
+                22 => {
+                    self.style.bold = false;
+                    self.style.dim = false;
+                }
+                23 => self.style.italic = false,
+                24 => self.style.underline = false,
+                27 => self.style.inverse = false,
+                30..=37 => self.style.foreground = Some(AnsiColor::Indexed((code - 30) as u8)),
+                38 => self.style.foreground = Self::extended_color(&mut codes),
+                39 => self.style.foreground = None,
+                40..=47 => self.style.background = Some(AnsiColor::Indexed((code - 40) as u8)),
+                48 => self.style.background = Self::extended_color(&mut codes),
+                49 => self.style.background = None,
+                90..=97 => self.style.foreground = Some(AnsiColor::Indexed((code - 90 + 8) as u8)),
+                100..=107 => {
+                    self.style.background = Some(AnsiColor::Indexed((code - 100 + 8) as u8))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Read the color following a 38 or 48 code: `5;n` or `2;r;g;b`
+    fn extended_color(codes: &mut impl Iterator<Item = u16>) -> Option<AnsiColor> {
+        let mut component = || codes.next().map(|value| value.min(255) as u8);
+        match component()? {
+            5 => component().map(AnsiColor::Indexed),
+            2 => Some(AnsiColor::Rgb(component()?, component()?, component()?)),
+            _ => None,
+        }
+    }
+}
+
+/// Text of a line with all escape sequences removed
+pub fn strip_ansi(line: &str) -> String {
+    AnsiParser::new()
+        .parse_line(line)
+        .into_iter()
+        .map(|span| span.text)
+        .collect()
+}
@@ -1,10 +1,17 @@
 //! Logging utilities
 
 use tracing::Level;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{Layer, Registry};
 
 /// Initialize logging
+///
+/// Logs go to stderr: in MCP server mode stdout carries JSON-RPC frames only.
 pub fn init_logging(level: Level) -> Result<(), LoggingError> {
     tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
         .with_max_level(level)
         .with_target(false)
         .with_thread_ids(true)
@@ -14,6 +21,30 @@ pub fn init_logging(level: Level) -> Result<(), LoggingError> {
     Ok(())
 }
 
+/// Initialize logging to stderr, also handing every event to `layer`
+///
+/// `level` only limits what is written to stderr; `layer` should carry its
+/// own per-layer filter, or it sees events of every level.
+pub fn init_logging_with_layer<L>(level: Level, layer: L) -> Result<(), LoggingError>
+where
+    L: Layer<Registry> + Send + Sync + 'static,
+{
+    let stderr = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_target(false)
+        .with_thread_ids(true)
+        .with_thread_names(true)
+        .with_filter(LevelFilter::from_level(level));
+
+    tracing_subscriber::registry()
+        .with(layer)
+        .with(stderr)
+        .try_init()
+        .map_err(|e| LoggingError::InitializationError {
+            message: e.to_string(),
+        })
+}
+
 /// Logging error
 #[derive(Debug, thiserror::Error)]
 pub enum LoggingError {
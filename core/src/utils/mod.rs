@@ -1,11 +1,13 @@
 //! Core utilities
 
+pub mod ansi;
 pub mod conversation_logger;
 pub mod error_handling;
 pub mod logging;
 pub mod serialization;
 
 // Re-export all utilities
+pub use ansi::*;
 pub use conversation_logger::*;
 pub use error_handling::*;
 pub use logging::*;
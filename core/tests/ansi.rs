@@ -0,0 +1,72 @@
+//! Parses terminal output with `AnsiParser`
+
+use ifm_ruta_core::utils::{strip_ansi, AnsiColor, AnsiParser, AnsiStyle, StyledSpan};
+
+fn span(text: &str, style: AnsiStyle) -> StyledSpan {
+    StyledSpan {
+        text: text.to_string(),
+        style,
+    }
+}
+
+#[test]
+fn plain_text_is_a_single_default_span() {
+    let spans = AnsiParser::new().parse_line("hello world");
+    assert_eq!(spans, [span("hello world", AnsiStyle::default())]);
+}
+
+#[test]
+fn colors_and_attributes_split_the_line() {
+    let spans = AnsiParser::new().parse_line("test \x1b[1;32mok\x1b[0m done");
+
+    let green = AnsiStyle {
+        foreground: Some(AnsiColor::Indexed(2)),
+        bold: true,
+        ..AnsiStyle::default()
+    };
+    assert_eq!(
+        spans,
+        [
+            span("test ", AnsiStyle::default()),
+            span("ok", green),
+            span(" done", AnsiStyle::default()),
+        ]
+    );
+}
+
+#[test]
+fn extended_and_bright_colors() {
+    let mut parser = AnsiParser::new();
+
+    let spans = parser.parse_line("\x1b[38;5;208ma\x1b[48;2;1;2;3mb\x1b[91mc");
+    assert_eq!(spans[0].style.foreground, Some(AnsiColor::Indexed(208)));
+    assert_eq!(spans[1].style.background, Some(AnsiColor::Rgb(1, 2, 3)));
+    assert_eq!(spans[2].style.foreground, Some(AnsiColor::Indexed(9)));
+
+    let spans = parser.parse_line("\x1b[0;38:2::10:20:30mx");
+    assert_eq!(spans[0].style.foreground, Some(AnsiColor::Rgb(10, 20, 30)));
+}
+
+#[test]
+fn style_carries_over_to_the_next_line() {
+    let mut parser = AnsiParser::new();
+    parser.parse_line("\x1b[31mstart");
+
+    let spans = parser.parse_line("still red\x1b[m");
+    assert_eq!(spans[0].style.foreground, Some(AnsiColor::Indexed(1)));
+    assert_eq!(parser.parse_line("plain")[0].style, AnsiStyle::default());
+}
+
+#[test]
+fn other_sequences_are_dropped() {
+    assert_eq!(
+        strip_ansi("\x1b]0;title\x07\x1b[2K\x1b(Bdone\x1b[1A\x08"),
+        "done"
+    );
+}
+
+#[test]
+fn carriage_return_keeps_the_last_redraw() {
+    assert_eq!(strip_ansi("10%\r50%\r100%"), "100%");
+    assert_eq!(strip_ansi("done\r"), "done");
+}
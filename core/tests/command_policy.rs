@@ -0,0 +1,189 @@
+//! Checks command lines against `CommandPolicy`
+
+use ifm_ruta_core::models::{AppSettings, CommandRule, PolicyAction, SecuritySettings};
+use ifm_ruta_core::services::{parse_command_line, CommandPolicy};
+use ifm_ruta_core::traits::ValidationRule;
+
+fn rule(action: PolicyAction, pattern: &str) -> CommandRule {
+    CommandRule {
+        action,
+        pattern: pattern.to_string(),
+    }
+}
+
+fn policy(rules: Vec<CommandRule>) -> CommandPolicy {
+    let mut settings: SecuritySettings = AppSettings::default().security;
+    settings.command_policy.rules = rules;
+    CommandPolicy::from_settings(&settings).unwrap()
+}
+
+#[test]
+fn command_lines_are_split_like_a_shell() {
+    assert_eq!(
+        parse_command_line(r#"git commit -m "fix: a \"quoted\" word" 'single quoted'"#).unwrap(),
+        [
+            "git",
+            "commit",
+            "-m",
+            r#"fix: a "quoted" word"#,
+            "single quoted"
+        ]
+    );
+    assert!(parse_command_line("echo 'unterminated").is_err());
+    assert!(parse_command_line("   ").is_err());
+}
+
+#[test]
+fn everything_is_allowed_without_rules() {
+    let decision = policy(vec![]).check("git log ..HEAD").unwrap();
+    assert!(decision.allowed);
+    assert_eq!(decision.matched_rule, None);
+}
+
+#[test]
+fn allow_rules_match_program_and_leading_arguments() {
+    let policy = policy(vec![
+        rule(PolicyAction::Allow, "cargo test*"),
+        rule(PolicyAction::Allow, "git diff"),
+    ]);
+
+    assert!(policy.check("cargo test").unwrap().allowed);
+    assert!(policy.check("cargo test-all --release").unwrap().allowed);
+    assert!(policy.check("git diff HEAD~1").unwrap().allowed);
+    assert!(!policy.check("git push").unwrap().allowed);
+    assert!(!policy.check("git").unwrap().allowed);
+    assert!(!policy.check("rm -rf target").unwrap().allowed);
+}
+
+#[test]
+fn deny_rules_win_over_allow_rules() {
+    let policy = policy(vec![
+        rule(PolicyAction::Allow, "cargo *"),
+        rule(PolicyAction::Deny, "cargo publish"),
+    ]);
+
+    assert!(policy.check("cargo build").unwrap().allowed);
+    let decision = policy.check("cargo publish --dry-run").unwrap();
+    assert!(!decision.allowed);
+    assert_eq!(
+        decision.matched_rule,
+        Some(rule(PolicyAction::Deny, "cargo publish"))
+    );
+    assert_eq!(decision.explanation, "Denied by rule `deny cargo publish`");
+}
+
+#[test]
+fn deny_rules_alone_allow_everything_else() {
+    let policy = policy(vec![rule(PolicyAction::Deny, "git push")]);
+
+    assert!(policy.check("git pull").unwrap().allowed);
+    assert!(!policy.check("git push origin main").unwrap().allowed);
+}
+
+#[test]
+fn deny_rules_see_through_paths_and_wrappers() {
+    let policy = policy(vec![rule(PolicyAction::Deny, "git push")]);
+
+    for command_line in [
+        "/usr/bin/git push",
+        "./git push origin",
+        "git -C . push",
+        "git --no-pager -c push.default=current push",
+        "env git push",
+        "env GIT_TRACE=1 nice -n 5 git push",
+        "env -i PATH=/usr/bin /usr/bin/git push",
+    ] {
+        let decision = policy.check(command_line).unwrap();
+        assert!(!decision.allowed, "`{}` was allowed", command_line);
+    }
+    assert!(policy.check("/usr/bin/git pull").unwrap().allowed);
+    assert!(policy.check("git push-to-deploy").unwrap().allowed);
+}
+
+#[test]
+fn deny_rules_look_inside_shell_command_strings() {
+    let policy = policy(vec![rule(PolicyAction::Deny, "git push")]);
+
+    for command_line in [
+        r#"sh -c "git push""#,
+        r#"bash -lc 'cd repo && git push origin main'"#,
+        r#"sh -c "bash -c 'git push'""#,
+        r#"env sh -c "git   push""#,
+    ] {
+        let decision = policy.check(command_line).unwrap();
+        assert!(!decision.allowed, "`{}` was allowed", command_line);
+    }
+    assert!(policy.check(r#"sh -c "git pull""#).unwrap().allowed);
+}
+
+#[test]
+fn allow_rules_still_match_the_program_as_typed() {
+    let policy = policy(vec![rule(PolicyAction::Allow, "git diff")]);
+
+    assert!(policy.check("git diff").unwrap().allowed);
+    assert!(!policy.check("./git diff").unwrap().allowed);
+    assert!(!policy.check("env git diff").unwrap().allowed);
+}
+
+#[test]
+fn default_action_applies_when_no_rule_matches() {
+    let mut settings = AppSettings::default().security;
+    settings.command_policy.default_action = Some(PolicyAction::Deny);
+    let policy = CommandPolicy::from_settings(&settings).unwrap();
+
+    let decision = policy.check("ls").unwrap();
+    assert!(!decision.allowed);
+    assert_eq!(decision.matched_rule, None);
+    assert!(decision.explanation.contains("no allow rule matches `ls`"));
+}
+
+#[test]
+fn allowed_commands_are_allow_rules_for_programs() {
+    let mut settings = AppSettings::default().security;
+    settings.allowed_commands = vec!["npm".to_string()];
+    let policy = CommandPolicy::from_settings(&settings).unwrap();
+
+    assert!(policy.check("npm run build").unwrap().allowed);
+    assert!(!policy.check("yarn build").unwrap().allowed);
+}
+
+#[test]
+fn shell_syntax_is_not_special() {
+    // Nothing runs through a shell, so these are plain arguments to `echo`
+    let policy = policy(vec![rule(PolicyAction::Allow, "echo")]);
+    assert!(policy.validate("echo a; rm -rf / | cat").is_ok());
+}
+
+#[test]
+fn invalid_patterns_are_rejected() {
+    let mut settings = AppSettings::default().security;
+    settings.command_policy.rules = vec![rule(PolicyAction::Allow, "cargo 'test")];
+    assert!(CommandPolicy::from_settings(&settings).is_err());
+}
+
+#[test]
+fn policy_reads_from_toml() {
+    let settings: SecuritySettings = toml::from_str(
+        r#"
+        allowed_commands = []
+        sandbox_mode = true
+        max_process_time = { secs = 60, nanos = 0 }
+
+        [command_policy]
+        default_action = "deny"
+
+        [[command_policy.rules]]
+        action = "allow"
+        pattern = "cargo test*"
+
+        [[command_policy.rules]]
+        action = "deny"
+        pattern = "cargo test --ignored"
+        "#,
+    )
+    .unwrap();
+    let policy = CommandPolicy::from_settings(&settings).unwrap();
+
+    assert!(policy.check("cargo test").unwrap().allowed);
+    assert!(!policy.check("cargo test --ignored").unwrap().allowed);
+}
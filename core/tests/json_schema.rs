@@ -0,0 +1,168 @@
+//! Checks values against JSON Schemas with `validate_against_schema`
+
+use serde_json::{json, Value};
+
+use ifm_ruta_core::services::validate_against_schema;
+use ifm_ruta_core::traits::ValidationError;
+
+/// Pointer and keyword of the violation, panicking if the value is valid
+fn violation(schema: &Value, instance: &Value) -> (String, String) {
+    match validate_against_schema(schema, instance) {
+        Err(ValidationError::SchemaViolation {
+            pointer, keyword, ..
+        }) => (pointer, keyword),
+        other => panic!("expected a schema violation, got {:?}", other),
+    }
+}
+
+fn schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "name": { "type": "string", "minLength": 1, "maxLength": 5 },
+            "count": { "type": "integer", "minimum": 0, "maximum": 10 },
+            "ratio": { "type": "number", "exclusiveMinimum": 0, "exclusiveMaximum": 1 },
+            "mode": { "type": "string", "enum": ["fast", "slow"] },
+            "tags": {
+                "type": "array",
+                "maxItems": 2,
+                "items": {
+                    "type": "object",
+                    "properties": { "label": { "type": "string" } },
+                    "required": ["label"],
+                    "additionalProperties": false
+                }
+            },
+            "nullable": { "type": ["string", "null"] }
+        },
+        "required": ["name"]
+    })
+}
+
+#[test]
+fn valid_values_pass() {
+    let instance = json!({
+        "name": "abc",
+        "count": 10,
+        "ratio": 0.5,
+        "mode": "slow",
+        "tags": [{ "label": "a" }],
+        "nullable": null,
+        "unknown": true
+    });
+    assert!(validate_against_schema(&schema(), &instance).is_ok());
+}
+
+#[test]
+fn missing_required_properties_point_at_the_property() {
+    assert_eq!(
+        violation(&schema(), &json!({})),
+        ("/name".to_string(), "required".to_string())
+    );
+    assert_eq!(
+        violation(&schema(), &json!({ "name": "a", "tags": [{}] })),
+        ("/tags/0/label".to_string(), "required".to_string())
+    );
+}
+
+#[test]
+fn types_are_checked() {
+    assert_eq!(
+        violation(&schema(), &json!({ "name": 1 })),
+        ("/name".to_string(), "type".to_string())
+    );
+    assert_eq!(
+        violation(&schema(), &json!({ "name": "a", "count": 1.5 })),
+        ("/count".to_string(), "type".to_string())
+    );
+    assert_eq!(
+        violation(&schema(), &json!({ "name": "a", "nullable": 3 })),
+        ("/nullable".to_string(), "type".to_string())
+    );
+    assert_eq!(
+        violation(&schema(), &json!([])),
+        ("".to_string(), "type".to_string())
+    );
+
+    let message = validate_against_schema(&schema(), &json!({ "name": true }))
+        .unwrap_err()
+        .to_string();
+    assert_eq!(
+        message,
+        "Invalid value at `/name`: expected string, found boolean"
+    );
+}
+
+#[test]
+fn ranges_and_lengths_are_checked() {
+    let cases = [
+        (json!({ "name": "" }), "/name", "minLength"),
+        (json!({ "name": "abcdef" }), "/name", "maxLength"),
+        (json!({ "name": "a", "count": -1 }), "/count", "minimum"),
+        (json!({ "name": "a", "count": 11 }), "/count", "maximum"),
+        (
+            json!({ "name": "a", "ratio": 0 }),
+            "/ratio",
+            "exclusiveMinimum",
+        ),
+        (
+            json!({ "name": "a", "ratio": 1 }),
+            "/ratio",
+            "exclusiveMaximum",
+        ),
+        (
+            json!({ "name": "a", "tags": [{ "label": "a" }, { "label": "b" }, { "label": "c" }] }),
+            "/tags",
+            "maxItems",
+        ),
+    ];
+    for (instance, pointer, keyword) in cases {
+        assert_eq!(
+            violation(&schema(), &instance),
+            (pointer.to_string(), keyword.to_string()),
+            "{}",
+            instance
+        );
+    }
+}
+
+#[test]
+fn enum_values_are_checked() {
+    let message = validate_against_schema(&schema(), &json!({ "name": "a", "mode": "medium" }))
+        .unwrap_err()
+        .to_string();
+    assert_eq!(
+        message,
+        r#"Invalid value at `/mode`: must be one of "fast", "slow""#
+    );
+}
+
+#[test]
+fn additional_properties_can_be_forbidden() {
+    assert_eq!(
+        violation(
+            &schema(),
+            &json!({ "name": "a", "tags": [{ "label": "a", "color": "red" }] })
+        ),
+        (
+            "/tags/0/color".to_string(),
+            "additionalProperties".to_string()
+        )
+    );
+
+    let typed = json!({ "type": "object", "additionalProperties": { "type": "integer" } });
+    assert!(validate_against_schema(&typed, &json!({ "a": 1 })).is_ok());
+    assert_eq!(
+        violation(&typed, &json!({ "a": "b" })),
+        ("/a".to_string(), "type".to_string())
+    );
+}
+
+#[test]
+fn pointer_tokens_are_escaped() {
+    let schema = json!({ "properties": { "a/b~c": { "type": "string" } } });
+    assert_eq!(
+        violation(&schema, &json!({ "a/b~c": 1 })),
+        ("/a~1b~0c".to_string(), "type".to_string())
+    );
+}
@@ -0,0 +1,100 @@
+//! Checks directories against `PathPolicy`
+
+use std::path::{Path, PathBuf};
+
+use ifm_ruta_core::services::PathPolicy;
+
+/// Fresh scratch directory holding `root/project`, `outside` and a file
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "ifm-ruta-path-policy-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("root").join("project")).unwrap();
+    std::fs::create_dir_all(dir.join("outside")).unwrap();
+    std::fs::write(dir.join("root").join("file.txt"), "").unwrap();
+    // The temporary directory itself may sit behind a symlink
+    dir.canonicalize().unwrap()
+}
+
+fn error(policy: &PathPolicy, path: &Path) -> String {
+    policy.check_directory(path).unwrap_err().to_string()
+}
+
+#[test]
+fn directories_inside_roots_are_accepted_in_canonical_form() {
+    let dir = scratch("inside");
+    let policy = PathPolicy::new([dir.join("root")]);
+
+    let checked = policy
+        .check_directory(&dir.join("root").join(".").join("project"))
+        .unwrap();
+    assert_eq!(checked, dir.join("root").join("project"));
+    assert!(policy.check_directory(&dir.join("root")).is_ok());
+}
+
+#[test]
+fn any_existing_directory_is_accepted_without_roots() {
+    let dir = scratch("no-roots");
+    assert!(PathPolicy::default()
+        .check_directory(&dir.join("outside"))
+        .is_ok());
+}
+
+#[test]
+fn relative_missing_and_file_paths_are_rejected() {
+    let dir = scratch("invalid");
+    let policy = PathPolicy::default();
+
+    assert!(error(&policy, Path::new("relative/dir")).contains("is not an absolute path"));
+    assert!(error(&policy, &dir.join("missing")).contains("does not exist"));
+    assert!(error(&policy, &dir.join("root").join("file.txt")).contains("is not a directory"));
+}
+
+#[test]
+fn parent_components_cannot_leave_the_roots() {
+    let dir = scratch("dotdot");
+    let policy = PathPolicy::new([dir.join("root")]);
+
+    let message = error(&policy, &dir.join("root").join("..").join("outside"));
+    assert!(
+        message.contains("is outside the allowed roots"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn roots_must_match_whole_components() {
+    let dir = scratch("prefix");
+    std::fs::create_dir_all(dir.join("root-sibling")).unwrap();
+    let policy = PathPolicy::new([dir.join("root")]);
+
+    assert!(policy.check_directory(&dir.join("root-sibling")).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks_cannot_leave_the_roots() {
+    let dir = scratch("symlink");
+    let link = dir.join("root").join("escape");
+    std::os::unix::fs::symlink(dir.join("outside"), &link).unwrap();
+    let policy = PathPolicy::new([dir.join("root")]);
+
+    let message = error(&policy, &link);
+    assert!(
+        message.contains("is outside the allowed roots"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn extra_roots_are_accepted_too() {
+    let dir = scratch("extra");
+    let policy = PathPolicy::new([dir.join("root")]).with_roots([dir.join("outside")]);
+
+    assert!(policy.check_directory(&dir.join("outside")).is_ok());
+}
@@ -0,0 +1,454 @@
+//! Runs real `/bin/sh` commands through `ProcessManagerImpl`
+
+#![cfg(unix)]
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use ifm_ruta_core::models::AppSettings;
+use ifm_ruta_core::services::{EventBusImpl, ProcessManagerImpl};
+use ifm_ruta_core::traits::{
+    Event, EventBus, EventError, EventListener, EventType, OutputStream, ProcessError,
+    ProcessHandle, ProcessManager, ProcessStats, ProcessStatus, SpawnOptions, TerminationReason,
+};
+use tokio::sync::broadcast::error::RecvError;
+
+/// Default settings with the sandbox off, which these tests do not exercise
+fn settings() -> AppSettings {
+    let mut settings = AppSettings::default();
+    settings.security.sandbox_mode = false;
+    settings
+}
+
+fn manager() -> ProcessManagerImpl {
+    ProcessManagerImpl::with_settings(&settings())
+}
+
+fn sh(manager: &ProcessManagerImpl, script: &str) -> ProcessHandle {
+    manager
+        .spawn_process(
+            "/bin/sh",
+            &["-c".to_string(), script.to_string()],
+            Path::new("/"),
+        )
+        .unwrap()
+}
+
+#[test]
+fn captures_output_and_exit_code() {
+    let manager = manager();
+    let handle = sh(&manager, "echo hello; echo oops >&2; exit 3");
+
+    let result = manager.wait_for_process(&handle).unwrap();
+    assert_eq!(result.exit_code, 3);
+    assert_eq!(result.status, ProcessStatus::Failed);
+    assert_eq!(result.stdout, "hello\n");
+    assert_eq!(result.stderr, "oops\n");
+
+    let output = manager.get_process_output(&handle).unwrap();
+    assert!(output.is_complete);
+    assert_eq!(output.stdout, "hello\n");
+}
+
+#[test]
+fn successful_command_completes() {
+    let manager = manager();
+    let handle = sh(&manager, "true");
+
+    let result = manager.wait_for_process(&handle).unwrap();
+    assert_eq!(result.exit_code, 0);
+    assert_eq!(result.status, ProcessStatus::Completed);
+}
+
+#[test]
+fn runs_in_the_given_directory() {
+    let dir = std::env::temp_dir().canonicalize().unwrap();
+    let manager = manager();
+    let handle = manager
+        .spawn_process("/bin/sh", &["-c".to_string(), "pwd".to_string()], &dir)
+        .unwrap();
+
+    let result = manager.wait_for_process(&handle).unwrap();
+    assert_eq!(result.stdout.trim_end(), dir.to_str().unwrap());
+}
+
+#[test]
+fn output_is_available_while_running() {
+    let manager = manager();
+    let handle = sh(&manager, "echo started; exec sleep 30");
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        let output = manager.get_process_output(&handle).unwrap();
+        assert!(!output.is_complete);
+        if output.stdout == "started\n" {
+            break;
+        }
+        assert!(Instant::now() < deadline, "no output from running process");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    manager.kill_process(&handle).unwrap();
+    manager.wait_for_process(&handle).unwrap();
+}
+
+#[test]
+fn kill_stops_the_process() {
+    let manager = manager();
+    let handle = sh(&manager, "exec sleep 30");
+
+    let started = Instant::now();
+    manager.kill_process(&handle).unwrap();
+    let result = manager.wait_for_process(&handle).unwrap();
+
+    assert_eq!(result.status, ProcessStatus::Killed);
+    assert_eq!(result.exit_code, -1);
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn killing_a_finished_process_is_a_no_op() {
+    let manager = manager();
+    let handle = sh(&manager, "exit 0");
+    manager.wait_for_process(&handle).unwrap();
+
+    manager.kill_process(&handle).unwrap();
+    let result = manager.wait_for_process(&handle).unwrap();
+    assert_eq!(result.status, ProcessStatus::Completed);
+}
+
+#[test]
+fn missing_program_fails_to_spawn() {
+    let manager = manager();
+    let error = manager
+        .spawn_process("/nonexistent/program", &[], Path::new("/"))
+        .unwrap_err();
+    assert!(matches!(error, ProcessError::ExecutionFailed { .. }));
+}
+
+#[test]
+fn unknown_handle_is_not_found() {
+    let other = manager();
+    let manager = manager();
+    let handle = sh(&other, "true");
+
+    assert!(matches!(
+        manager.get_process_output(&handle),
+        Err(ProcessError::ProcessNotFound { .. })
+    ));
+    assert!(matches!(
+        manager.kill_process(&handle),
+        Err(ProcessError::ProcessNotFound { .. })
+    ));
+}
+
+#[test]
+fn try_wait_does_not_block() {
+    let manager = manager();
+    let handle = sh(&manager, "exec sleep 30");
+    assert!(manager.try_wait_process(&handle).unwrap().is_none());
+
+    manager.kill_process(&handle).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let result = loop {
+        if let Some(result) = manager.try_wait_process(&handle).unwrap() {
+            break result;
+        }
+        assert!(Instant::now() < deadline, "killed process did not complete");
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    assert_eq!(result.status, ProcessStatus::Killed);
+}
+
+#[test]
+fn removed_processes_are_forgotten() {
+    let manager = manager();
+    let handle = sh(&manager, "echo done");
+    manager.wait_for_process(&handle).unwrap();
+
+    manager.remove_process(&handle).unwrap();
+    assert!(matches!(
+        manager.get_process_output(&handle),
+        Err(ProcessError::ProcessNotFound { .. })
+    ));
+    assert!(matches!(
+        manager.remove_process(&handle),
+        Err(ProcessError::ProcessNotFound { .. })
+    ));
+}
+
+#[test]
+fn removing_a_running_process_kills_it() {
+    let manager = manager();
+    let handle = sh(&manager, "echo started; exec sleep 30");
+
+    let started = Instant::now();
+    manager.remove_process(&handle).unwrap();
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert!(matches!(
+        manager.process_stats(&handle),
+        Err(ProcessError::ProcessNotFound { .. })
+    ));
+}
+
+#[test]
+fn subscription_streams_lines_in_order() {
+    let manager = manager();
+    let handle = sh(&manager, "echo one; echo two >&2; echo three");
+    let mut subscription = manager.subscribe_output(&handle).unwrap();
+
+    let mut lines: Vec<(OutputStream, String)> = subscription
+        .backlog
+        .iter()
+        .map(|line| (line.stream, line.text.clone()))
+        .collect();
+    loop {
+        match subscription.receiver.blocking_recv() {
+            Ok(line) => lines.push((line.stream, line.text)),
+            Err(RecvError::Closed) => break,
+            Err(RecvError::Lagged(_)) => panic!("subscriber lagged"),
+        }
+    }
+
+    // Streams are read separately, so only the order within each is guaranteed
+    let stdout: Vec<_> = lines
+        .iter()
+        .filter(|(stream, _)| *stream == OutputStream::Stdout)
+        .map(|(_, text)| text.as_str())
+        .collect();
+    let stderr: Vec<_> = lines
+        .iter()
+        .filter(|(stream, _)| *stream == OutputStream::Stderr)
+        .map(|(_, text)| text.as_str())
+        .collect();
+    assert_eq!(stdout, ["one", "three"]);
+    assert_eq!(stderr, ["two"]);
+}
+
+#[test]
+fn late_subscribers_get_the_backlog_and_a_closed_receiver() {
+    let manager = manager();
+    let handle = sh(&manager, "echo done");
+    manager.wait_for_process(&handle).unwrap();
+
+    let mut subscription = manager.subscribe_output(&handle).unwrap();
+    assert_eq!(subscription.backlog.len(), 1);
+    assert_eq!(subscription.backlog[0].text, "done");
+    assert!(matches!(
+        subscription.receiver.blocking_recv(),
+        Err(RecvError::Closed)
+    ));
+}
+
+#[test]
+fn output_buffer_keeps_only_the_latest_lines() {
+    let mut settings = settings();
+    settings.performance.cache_size = 10;
+    let manager = ProcessManagerImpl::with_settings(&settings);
+    let handle = sh(&manager, "for i in 1 2 3 4 5 6 7 8 9; do echo line$i; done");
+
+    let result = manager.wait_for_process(&handle).unwrap();
+    assert_eq!(result.stdout, "line8\nline9\n");
+}
+
+/// Records the types of events it receives
+struct Recorder(Arc<Mutex<Vec<EventType>>>);
+
+impl EventListener for Recorder {
+    fn handle_event(&self, event: &Event) -> Result<(), EventError> {
+        self.0.lock().unwrap().push(event.event_type.clone());
+        Ok(())
+    }
+
+    fn listener_id(&self) -> &str {
+        "recorder"
+    }
+}
+
+#[test]
+fn publishes_spawn_and_completion_events() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut event_bus = EventBusImpl::new();
+    for event_type in [EventType::ProcessSpawned, EventType::ProcessCompleted] {
+        event_bus.subscribe(event_type, Box::new(Recorder(events.clone())));
+    }
+
+    let manager = manager().with_event_bus(Arc::new(event_bus));
+    let handle = sh(&manager, "true");
+    manager.wait_for_process(&handle).unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while events.lock().unwrap().len() < 2 {
+        assert!(Instant::now() < deadline, "completion event not published");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(
+        *events.lock().unwrap(),
+        [EventType::ProcessSpawned, EventType::ProcessCompleted]
+    );
+}
+
+fn with_time_limit(max_process_time: Duration) -> ProcessManagerImpl {
+    let mut settings = settings();
+    settings.security.max_process_time = max_process_time;
+    ProcessManagerImpl::with_settings(&settings)
+}
+
+#[test]
+fn processes_over_the_time_limit_are_terminated() {
+    let manager = with_time_limit(Duration::from_millis(200));
+    let handle = sh(&manager, "sleep 30");
+
+    let started = Instant::now();
+    let result = manager.wait_for_process(&handle).unwrap();
+
+    assert_eq!(result.status, ProcessStatus::Killed);
+    assert_eq!(result.termination_reason, Some(TerminationReason::Timeout));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn processes_ignoring_sigterm_are_killed_after_the_grace_period() {
+    let manager = with_time_limit(Duration::from_millis(200));
+    let handle = sh(&manager, "trap '' TERM; while true; do sleep 1; done");
+
+    let started = Instant::now();
+    let result = manager.wait_for_process(&handle).unwrap();
+
+    assert_eq!(result.status, ProcessStatus::Killed);
+    assert_eq!(result.termination_reason, Some(TerminationReason::Timeout));
+    assert!(started.elapsed() < Duration::from_secs(15));
+}
+
+#[test]
+fn kill_reaches_grandchildren() {
+    let manager = manager();
+    // The background sleep keeps stdout open until it is killed as well
+    let handle = sh(&manager, "sleep 30 & wait");
+
+    let started = Instant::now();
+    manager.kill_process(&handle).unwrap();
+    let result = manager.wait_for_process(&handle).unwrap();
+
+    assert_eq!(
+        result.termination_reason,
+        Some(TerminationReason::Requested)
+    );
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn zero_time_limit_disables_the_watchdog() {
+    let manager = with_time_limit(Duration::ZERO);
+    let handle = sh(&manager, "sleep 1");
+
+    let result = manager.wait_for_process(&handle).unwrap();
+    assert_eq!(result.status, ProcessStatus::Completed);
+    assert_eq!(result.termination_reason, None);
+}
+
+/// Poll the stats of a running process until `ready` accepts them
+fn wait_for_stats(
+    manager: &ProcessManagerImpl,
+    handle: &ProcessHandle,
+    ready: impl Fn(&ProcessStats) -> bool,
+) -> ProcessStats {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        let stats = manager.process_stats(handle).unwrap();
+        if ready(&stats) {
+            return stats;
+        }
+        assert!(Instant::now() < deadline, "no usable sample: {:?}", stats);
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+#[test]
+fn stats_cover_the_whole_process_tree() {
+    let manager = manager();
+    let handle = sh(&manager, "sleep 30 & sleep 30 & wait");
+
+    let stats = wait_for_stats(&manager, &handle, |stats| stats.process_count == 3);
+    assert!(stats.memory_bytes > 0);
+    assert!(stats.peak_memory_bytes >= stats.memory_bytes);
+
+    manager.kill_process(&handle).unwrap();
+    let result = manager.wait_for_process(&handle).unwrap();
+    let stats = manager.process_stats(&handle).unwrap();
+    assert_eq!(stats.process_count, 0);
+    assert_eq!(stats.memory_bytes, 0);
+    assert!(stats.peak_memory_bytes > 0);
+    assert_eq!(stats.runtime, result.duration);
+}
+
+#[test]
+fn processes_over_the_memory_limit_are_terminated() {
+    let mut settings = settings();
+    settings.performance.max_memory_usage = 16 * 1024 * 1024;
+    let manager = ProcessManagerImpl::with_settings(&settings);
+    // The shell holds the whole 64MB string in memory while it sleeps
+    let handle = sh(
+        &manager,
+        "x=$(head -c 67108864 /dev/zero | tr '\\0' a); sleep 30",
+    );
+
+    let started = Instant::now();
+    let result = manager.wait_for_process(&handle).unwrap();
+
+    assert_eq!(result.status, ProcessStatus::Killed);
+    assert_eq!(
+        result.termination_reason,
+        Some(TerminationReason::MemoryLimit)
+    );
+    assert!(started.elapsed() < Duration::from_secs(20));
+}
+
+fn sh_on_terminal(manager: &ProcessManagerImpl, script: &str) -> ProcessHandle {
+    manager
+        .spawn_process_with_options(
+            "/bin/sh",
+            &["-c".to_string(), script.to_string()],
+            Path::new("/"),
+            &SpawnOptions { pty: true },
+        )
+        .unwrap()
+}
+
+#[test]
+fn pty_processes_run_on_a_terminal() {
+    let manager = manager();
+    let handle = sh_on_terminal(&manager, "test -t 1 && echo tty; echo err >&2");
+
+    let result = manager.wait_for_process(&handle).unwrap();
+    assert_eq!(result.status, ProcessStatus::Completed);
+    assert_eq!(result.stdout, "tty\nerr\n");
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn pty_processes_read_written_input() {
+    let manager = manager();
+    let handle = sh_on_terminal(&manager, "stty -echo; read line; echo got $line");
+
+    manager.write_stdin(&handle, b"hello\n").unwrap();
+    let result = manager.wait_for_process(&handle).unwrap();
+    assert!(
+        result.stdout.ends_with("got hello\n"),
+        "{:?}",
+        result.stdout
+    );
+}
+
+#[test]
+fn piped_processes_reject_input() {
+    let manager = manager();
+    let handle = sh(&manager, "exec sleep 30");
+
+    assert!(matches!(
+        manager.write_stdin(&handle, b"hello\n"),
+        Err(ProcessError::ExecutionFailed { .. })
+    ));
+    manager.kill_process(&handle).unwrap();
+}
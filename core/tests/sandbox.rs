@@ -0,0 +1,143 @@
+//! Runs commands under `security.sandbox_mode` on Linux
+//!
+//! Kernels without unprivileged user namespaces or Landlock refuse to start
+//! sandboxed commands; the tests are skipped there.
+
+#![cfg(target_os = "linux")]
+
+use std::path::{Path, PathBuf};
+
+use ifm_ruta_core::models::AppSettings;
+use ifm_ruta_core::services::ProcessManagerImpl;
+use ifm_ruta_core::traits::{ProcessError, ProcessManager, ProcessResult, SpawnOptions};
+
+/// Fresh directory under the system temp dir
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "ifm-ruta-sandbox-{}-{}",
+        name,
+        uuid::Uuid::new_v4()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn manager(sandbox_mode: bool) -> ProcessManagerImpl {
+    let mut settings = AppSettings::default();
+    settings.security.sandbox_mode = sandbox_mode;
+    ProcessManagerImpl::with_settings(&settings)
+}
+
+/// Run a script in `cwd`, or `None` if this kernel cannot sandbox it
+fn sh(manager: &ProcessManagerImpl, script: &str, cwd: &Path) -> Option<ProcessResult> {
+    match manager.spawn_process("/bin/sh", &["-c".to_string(), script.to_string()], cwd) {
+        Ok(handle) => Some(manager.wait_for_process(&handle).unwrap()),
+        Err(ProcessError::PermissionDenied { message }) => {
+            eprintln!("skipping: {}", message);
+            None
+        }
+        Err(e) => panic!("failed to spawn: {}", e),
+    }
+}
+
+#[test]
+fn project_directory_is_writable() {
+    let project = temp_dir("project");
+    let Some(result) = sh(&manager(true), "echo ok > inside.txt", &project) else {
+        return;
+    };
+
+    assert_eq!(result.exit_code, 0, "{}", result.stderr);
+    assert!(project.join("inside.txt").exists());
+}
+
+#[test]
+fn writes_outside_the_project_are_denied() {
+    let project = temp_dir("project");
+    let outside = temp_dir("outside").join("outside.txt");
+    let script = format!("echo leak > '{}'", outside.display());
+    let Some(result) = sh(&manager(true), &script, &project) else {
+        return;
+    };
+
+    assert_ne!(result.exit_code, 0);
+    assert!(!outside.exists());
+}
+
+#[test]
+fn network_is_unavailable() {
+    let project = temp_dir("project");
+    // Only the loopback interface exists in a new network namespace
+    let Some(result) = sh(&manager(true), "tail -n +3 /proc/net/dev", &project) else {
+        return;
+    };
+
+    let interfaces: Vec<&str> = result
+        .stdout
+        .lines()
+        .filter_map(|line| line.split(':').next())
+        .map(str::trim)
+        .collect();
+    assert_eq!(interfaces, ["lo"]);
+}
+
+#[test]
+fn memory_limit_caps_the_data_segment() {
+    let project = temp_dir("project");
+    let Some(result) = sh(&manager(true), "ulimit -d", &project) else {
+        return;
+    };
+
+    let expected_kib = AppSettings::default().performance.max_memory_usage / 1024;
+    assert_eq!(result.stdout.trim(), expected_kib.to_string());
+}
+
+#[test]
+fn disabled_memory_limit_leaves_the_data_segment_unlimited() {
+    let project = temp_dir("project");
+    let mut settings = AppSettings::default();
+    settings.performance.max_memory_usage = 0;
+    let manager = ProcessManagerImpl::with_settings(&settings);
+    let Some(result) = sh(&manager, "ulimit -d", &project) else {
+        return;
+    };
+
+    assert_eq!(result.stdout.trim(), "unlimited");
+}
+
+#[test]
+fn disabling_the_sandbox_allows_writes_anywhere() {
+    let project = temp_dir("project");
+    let outside = temp_dir("outside").join("outside.txt");
+    let script = format!("echo ok > '{}'", outside.display());
+    let result = sh(&manager(false), &script, &project).unwrap();
+
+    assert_eq!(result.exit_code, 0, "{}", result.stderr);
+    assert!(outside.exists());
+}
+
+#[test]
+fn pty_processes_can_be_sandboxed() {
+    let project = temp_dir("project");
+    let manager = manager(true);
+    let handle = match manager.spawn_process_with_options(
+        "/bin/sh",
+        &[
+            "-c".to_string(),
+            "test -t 1 && echo ok > /dev/tty".to_string(),
+        ],
+        &project,
+        &SpawnOptions { pty: true },
+    ) {
+        Ok(handle) => handle,
+        Err(ProcessError::PermissionDenied { message }) => {
+            eprintln!("skipping: {}", message);
+            return;
+        }
+        Err(e) => panic!("failed to spawn: {}", e),
+    };
+
+    let result = manager.wait_for_process(&handle).unwrap();
+    assert_eq!(result.exit_code, 0, "{}", result.stdout);
+    assert_eq!(result.stdout, "ok\n");
+}
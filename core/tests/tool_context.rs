@@ -0,0 +1,108 @@
+//! Runs tools with a `ToolContext`
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use ifm_ruta_core::services::{EventBusImpl, ProcessManagerImpl, SettingsManagerImpl};
+use ifm_ruta_core::traits::{
+    CancellationToken, ProgressReporter, ProgressSink, Tool, ToolContext, ToolError,
+};
+
+/// Progress, total and message of an update
+type Update = (f64, Option<f64>, Option<String>);
+
+/// Records every progress update
+#[derive(Default)]
+struct RecordingSink {
+    updates: Mutex<Vec<Update>>,
+}
+
+impl ProgressSink for RecordingSink {
+    fn report(&self, progress: f64, total: Option<f64>, message: Option<&str>) {
+        self.updates
+            .lock()
+            .unwrap()
+            .push((progress, total, message.map(str::to_string)));
+    }
+}
+
+/// Reports progress, then waits until it is cancelled
+struct WaitingTool;
+
+#[async_trait::async_trait]
+impl Tool for WaitingTool {
+    fn name(&self) -> &str {
+        "wait"
+    }
+
+    fn description(&self) -> &str {
+        "Waits until cancelled"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({ "type": "object" })
+    }
+
+    async fn execute(&self, _input: Value, context: &ToolContext) -> Result<Value, ToolError> {
+        context.progress.report(1.0, Some(2.0), Some("waiting"));
+        context.cancellation.cancelled().await;
+        Err(ToolError::Cancelled)
+    }
+}
+
+fn context() -> ToolContext {
+    ToolContext::new(
+        Arc::new(SettingsManagerImpl::new()),
+        Arc::new(ProcessManagerImpl::new()),
+        Arc::new(EventBusImpl::new()),
+    )
+}
+
+#[tokio::test]
+async fn tools_report_progress_and_observe_cancellation() {
+    let sink = Arc::new(RecordingSink::default());
+    let context = ToolContext {
+        request_id: json!(7),
+        progress: ProgressReporter::new(sink.clone()),
+        ..context()
+    };
+    let cancellation = context.cancellation.clone();
+
+    let task = tokio::spawn(async move { WaitingTool.execute(json!({}), &context).await });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!task.is_finished());
+
+    cancellation.cancel();
+    let outcome = tokio::time::timeout(Duration::from_secs(5), task)
+        .await
+        .expect("tool did not notice the cancellation")
+        .unwrap();
+    assert!(matches!(outcome, Err(ToolError::Cancelled)));
+    assert_eq!(
+        *sink.updates.lock().unwrap(),
+        [(1.0, Some(2.0), Some("waiting".to_string()))]
+    );
+}
+
+#[tokio::test]
+async fn cancelled_returns_at_once_for_cancelled_tokens() {
+    let token = CancellationToken::new();
+    token.cancel();
+    tokio::time::timeout(Duration::from_secs(1), token.cancelled())
+        .await
+        .unwrap();
+}
+
+#[test]
+fn progress_is_dropped_when_nobody_listens() {
+    let reporter = ProgressReporter::default();
+    assert!(!reporter.is_enabled());
+    reporter.report(1.0, None, None);
+
+    let context = context();
+    assert_eq!(context.request_id, Value::Null);
+    assert!(!context.progress.is_enabled());
+    assert!(!context.cancellation.is_cancelled());
+}
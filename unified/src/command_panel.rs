@@ -0,0 +1,672 @@
+//! Command section of the feedback window
+//!
+//! Runs project commands through the `ProcessManager` in the project
+//! directory and keeps a log of everything that ran, which is returned to
+//! the agent together with the feedback.
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use ifm_ruta_core::{
+    models::{AutoRunCommand, ProjectSettings},
+    services::{parse_command_line, CommandPolicy, SettingsManagerImpl},
+    traits::{
+        OutputLine, OutputSubscription, ProcessHandle, ProcessManager, ProcessStatus,
+        SettingsManager, SpawnOptions, TerminationReason,
+    },
+    utils::AnsiParser,
+};
+use tokio::sync::broadcast::error::TryRecvError;
+
+use crate::terminal_view::{TerminalView, MAX_LINES};
+
+/// How often the output of a running command is refreshed
+const OUTPUT_REFRESH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Human-readable size, e.g. `12.3 MB`
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Most recent lines of text, like the view keeps on screen
+#[derive(Default)]
+struct LogTail {
+    lines: VecDeque<String>,
+    /// Lines dropped from the front to stay within `MAX_LINES`
+    dropped: usize,
+}
+
+impl LogTail {
+    fn push(&mut self, line: String) {
+        if self.lines.len() == MAX_LINES {
+            self.lines.pop_front();
+            self.dropped += 1;
+        }
+        self.lines.push_back(line);
+    }
+
+    fn append(&mut self, other: LogTail) {
+        self.dropped += other.dropped;
+        for line in other.lines {
+            self.push(line);
+        }
+    }
+
+    /// The kept lines, after a note on how many were dropped
+    fn text(&self) -> String {
+        let mut text = String::new();
+        if self.dropped > 0 {
+            text.push_str(&format!("[... {} earlier lines dropped]\n", self.dropped));
+        }
+        for line in &self.lines {
+            text.push_str(line);
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// Command that is still running
+struct RunningCommand {
+    handle: ProcessHandle,
+    command_line: String,
+    started_at: Instant,
+    subscription: OutputSubscription,
+    /// Whether the command runs on a pseudo-terminal and accepts input
+    pty: bool,
+    parser: AnsiParser,
+    /// Interleaved stdout and stderr received so far, without escape sequences
+    output: LogTail,
+}
+
+impl RunningCommand {
+    fn push_line(&mut self, line: &OutputLine, view: &mut TerminalView) {
+        let spans = self.parser.parse_line(&line.text);
+        self.output
+            .push(spans.iter().map(|span| span.text.as_str()).collect());
+        view.push_spans(spans);
+    }
+
+    /// Take in lines that arrived since the last frame; `true` once output has ended
+    fn drain(&mut self, view: &mut TerminalView) -> bool {
+        loop {
+            match self.subscription.receiver.try_recv() {
+                Ok(line) => self.push_line(&line, view),
+                Err(TryRecvError::Empty) => return false,
+                Err(TryRecvError::Lagged(skipped)) => {
+                    let note = format!("[... {} lines skipped]", skipped);
+                    view.push_plain(&note);
+                    self.output.push(note);
+                }
+                Err(TryRecvError::Closed) => return true,
+            }
+        }
+    }
+}
+
+/// Collapsible panel for running commands in the project directory
+pub struct CommandPanel {
+    project_directory: PathBuf,
+    project_settings: ProjectSettings,
+    /// The user's own settings, which hold the commands allowed to run on open
+    settings_manager: SettingsManagerImpl,
+    process_manager: Arc<dyn ProcessManager>,
+    /// Which commands may run; an invalid policy in the settings blocks all of them
+    policy: Result<CommandPolicy, String>,
+    command: String,
+    /// Whether the user chose to run the command whenever the window opens
+    auto_run: bool,
+    running: Option<RunningCommand>,
+    /// Text typed for the running command's stdin
+    input: String,
+    /// What the panel shows: every command line, its output and its outcome
+    view: TerminalView,
+    logs: LogTail,
+    commands_executed: Vec<String>,
+    error_message: Option<String>,
+}
+
+impl CommandPanel {
+    /// Create the panel, starting the project's default command if the user chose to
+    ///
+    /// Only the user's own settings can make a command run on open; the
+    /// project's settings merely pre-fill it.
+    pub fn new(
+        project_directory: PathBuf,
+        process_manager: Arc<dyn ProcessManager>,
+        settings_manager: SettingsManagerImpl,
+    ) -> Self {
+        let project_settings = SettingsManagerImpl::load_project_settings(&project_directory)
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to load project settings, using defaults: {}", e);
+                ProjectSettings::default()
+            });
+
+        let security = settings_manager.get_settings().security;
+        let policy = CommandPolicy::from_settings(&security).map_err(|e| {
+            tracing::warn!("Invalid command policy, commands are disabled: {}", e);
+            format!("Commands are disabled until the settings are fixed: {}", e)
+        });
+
+        let command = project_settings.default_command.clone().unwrap_or_default();
+        let auto_run = !command.trim().is_empty()
+            && security.auto_run_commands.contains(&AutoRunCommand {
+                project_directory: project_directory.clone(),
+                command: command.trim().to_string(),
+            });
+
+        let mut panel = Self {
+            command,
+            auto_run,
+            project_directory,
+            project_settings,
+            settings_manager,
+            process_manager,
+            policy,
+            running: None,
+            input: String::new(),
+            view: TerminalView::default(),
+            logs: LogTail::default(),
+            commands_executed: Vec::new(),
+            error_message: None,
+        };
+
+        if panel.auto_run {
+            panel.run_command();
+        }
+        panel
+    }
+
+    /// Output of the commands run so far, without the oldest lines once
+    /// there are more than the view keeps
+    pub fn logs(&self) -> String {
+        self.logs.text()
+    }
+
+    /// Command lines run so far, in order
+    pub fn commands_executed(&self) -> &[String] {
+        &self.commands_executed
+    }
+
+    /// Stop a command that is still running and keep what it printed
+    pub fn shutdown(&mut self) {
+        if let Some(mut running) = self.running.take() {
+            running.drain(&mut self.view);
+            if let Err(e) = self.process_manager.remove_process(&running.handle) {
+                tracing::warn!("Failed to stop {}: {}", running.command_line, e);
+            }
+            self.logs.append(running.output);
+            self.note("[stopped when the feedback window closed]");
+        }
+    }
+
+    /// Add a line of the panel's own to both the log and the view
+    fn note(&mut self, text: &str) {
+        self.logs.push(text.to_string());
+        self.view.push_plain(text);
+    }
+
+    /// Start the command typed into the panel
+    fn run_command(&mut self) {
+        if self.running.is_some() {
+            return;
+        }
+
+        let command_line = self.command.trim().to_string();
+        if command_line.is_empty() {
+            self.error_message = Some("Enter a command to run".to_string());
+            return;
+        }
+        let policy = match &self.policy {
+            Ok(policy) => policy,
+            Err(e) => {
+                self.error_message = Some(e.clone());
+                return;
+            }
+        };
+        let mut words = match parse_command_line(&command_line) {
+            Ok(words) => words,
+            Err(e) => {
+                self.error_message = Some(e.to_string());
+                return;
+            }
+        };
+        let decision = policy.evaluate(&words);
+        if !decision.allowed {
+            self.error_message = Some(decision.explanation);
+            return;
+        }
+        tracing::debug!("Running {}: {}", command_line, decision.explanation);
+
+        let program = words.remove(0);
+        let args = words;
+
+        let options = SpawnOptions {
+            pty: self.project_settings.use_pty,
+        };
+        let spawned = self
+            .process_manager
+            .spawn_process_with_options(&program, &args, &self.project_directory, &options)
+            .and_then(
+                |handle| match self.process_manager.subscribe_output(&handle) {
+                    Ok(subscription) => Ok((handle, subscription)),
+                    Err(e) => {
+                        let _ = self.process_manager.kill_process(&handle);
+                        Err(e)
+                    }
+                },
+            );
+
+        match spawned {
+            Ok((handle, subscription)) => {
+                self.error_message = None;
+                self.note(&format!("$ {}", command_line));
+                self.commands_executed.push(command_line.clone());
+                self.remember_command(&command_line);
+
+                let backlog = subscription.backlog.clone();
+                let mut running = RunningCommand {
+                    handle,
+                    command_line,
+                    started_at: Instant::now(),
+                    subscription,
+                    pty: options.pty,
+                    parser: AnsiParser::new(),
+                    output: LogTail::default(),
+                };
+                for line in &backlog {
+                    running.push_line(line, &mut self.view);
+                }
+                self.running = Some(running);
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Failed to run {}: {}", program, e));
+            }
+        }
+    }
+
+    /// Pick up new output and collect the command once it finishes
+    fn poll(&mut self) {
+        let Some(running) = &mut self.running else {
+            return;
+        };
+        if !running.drain(&mut self.view) {
+            return;
+        }
+
+        // Output has ended, but the process may still be running with its
+        // output closed; never block the UI waiting for it
+        let Some(finished) = self
+            .process_manager
+            .try_wait_process(&running.handle)
+            .transpose()
+        else {
+            return;
+        };
+        let Some(running) = self.running.take() else {
+            return;
+        };
+        self.logs.append(running.output);
+        match finished {
+            Ok(result) => {
+                let outcome = match (result.status, result.termination_reason) {
+                    (_, Some(TerminationReason::Timeout)) => "timed out".to_string(),
+                    (_, Some(TerminationReason::MemoryLimit)) => {
+                        "stopped at the memory limit".to_string()
+                    }
+                    (ProcessStatus::Killed, _) => "stopped".to_string(),
+                    _ => format!("exit code {}", result.exit_code),
+                };
+                let peak = match self.process_manager.process_stats(&running.handle) {
+                    Ok(stats) if stats.peak_memory_bytes > 0 => {
+                        format!(", peak memory {}", format_bytes(stats.peak_memory_bytes))
+                    }
+                    _ => String::new(),
+                };
+                self.note(&format!(
+                    "[{} after {:.1}s{}]",
+                    outcome,
+                    result.duration.as_secs_f64(),
+                    peak
+                ));
+            }
+            Err(e) => {
+                self.note(&format!("[failed: {}]", e));
+            }
+        }
+        if let Err(e) = self.process_manager.remove_process(&running.handle) {
+            tracing::warn!("Failed to release {}: {}", running.command_line, e);
+        }
+    }
+
+    /// Stop the running command; its output is collected on the next poll
+    fn stop_command(&mut self) {
+        if let Some(running) = &self.running {
+            if let Err(e) = self.process_manager.kill_process(&running.handle) {
+                self.error_message = Some(format!("Failed to stop command: {}", e));
+            }
+        }
+    }
+
+    /// Send the typed input to the running command, followed by a newline
+    fn send_input(&mut self) {
+        let Some(running) = &self.running else {
+            return;
+        };
+        let mut data = std::mem::take(&mut self.input);
+        data.push('\n');
+        if let Err(e) = self
+            .process_manager
+            .write_stdin(&running.handle, data.as_bytes())
+        {
+            self.error_message = Some(format!("Failed to send input: {}", e));
+        }
+    }
+
+    /// Make the last command the project's default
+    fn remember_command(&mut self, command_line: &str) {
+        self.project_settings.default_command = Some(command_line.to_string());
+        self.save_project_settings();
+        if self.auto_run {
+            self.save_auto_run();
+        }
+    }
+
+    /// Record in the user's settings whether the default command runs on open
+    fn save_auto_run(&mut self) {
+        let project_directory = self.project_directory.clone();
+        let command = self
+            .auto_run
+            .then(|| self.project_settings.default_command.clone())
+            .flatten();
+        let saved = self.settings_manager.update_settings(|settings| {
+            let auto_run_commands = &mut settings.security.auto_run_commands;
+            auto_run_commands.retain(|entry| entry.project_directory != project_directory);
+            if let Some(command) = command {
+                auto_run_commands.push(AutoRunCommand {
+                    project_directory,
+                    command,
+                });
+            }
+        });
+        if let Err(e) = saved {
+            tracing::warn!("Failed to save settings: {}", e);
+        }
+    }
+
+    fn save_project_settings(&self) {
+        if let Err(e) = SettingsManagerImpl::save_project_settings(
+            &self.project_directory,
+            &self.project_settings,
+        ) {
+            tracing::warn!("Failed to save project settings: {}", e);
+        }
+    }
+
+    /// Render the panel
+    pub fn show(&mut self, ui: &mut eframe::egui::Ui) {
+        self.poll();
+        if self.running.is_some() {
+            ui.ctx().request_repaint_after(OUTPUT_REFRESH_INTERVAL);
+        }
+
+        let response = eframe::egui::CollapsingHeader::new(
+            eframe::egui::RichText::new("⚙ Commands")
+                .size(14.0)
+                .strong(),
+        )
+        .default_open(self.project_settings.ui_state.show_command_section)
+        .show(ui, |ui| self.show_contents(ui));
+
+        // Remember per project whether the section is expanded
+        if response.header_response.clicked() {
+            self.project_settings.ui_state.show_command_section =
+                !self.project_settings.ui_state.show_command_section;
+            self.save_project_settings();
+        }
+    }
+
+    fn show_contents(&mut self, ui: &mut eframe::egui::Ui) {
+        ui.horizontal(|ui| {
+            let input = ui.add_enabled(
+                self.running.is_none(),
+                eframe::egui::TextEdit::singleline(&mut self.command)
+                    .hint_text("e.g. cargo test")
+                    .font(eframe::egui::TextStyle::Monospace)
+                    .desired_width(ui.available_width() - 120.0),
+            );
+            let enter_pressed =
+                input.lost_focus() && ui.input(|i| i.key_pressed(eframe::egui::Key::Enter));
+
+            if self.running.is_some() {
+                if ui.button("⏹ Stop").clicked() {
+                    self.stop_command();
+                }
+            } else if ui.button("▶ Run").clicked() || enter_pressed {
+                self.run_command();
+            }
+        });
+
+        if ui
+            .add_enabled(
+                self.project_settings.default_command.is_some(),
+                eframe::egui::Checkbox::new(
+                    &mut self.auto_run,
+                    "Run the last command automatically when the feedback window opens",
+                ),
+            )
+            .changed()
+        {
+            self.save_auto_run();
+        }
+        if ui
+            .add_enabled(
+                cfg!(unix) && self.running.is_none(),
+                eframe::egui::Checkbox::new(
+                    &mut self.project_settings.use_pty,
+                    "Run in a terminal (keeps colors, accepts input)",
+                ),
+            )
+            .changed()
+        {
+            self.save_project_settings();
+        }
+
+        if let Some(error) = &self.error_message {
+            ui.label(
+                eframe::egui::RichText::new(error)
+                    .size(12.0)
+                    .color(eframe::egui::Color32::from_rgb(200, 60, 60)),
+            );
+        }
+
+        if let Some(running) = &self.running {
+            let mut status = format!(
+                "Running {} ({}s)",
+                running.command_line,
+                running.started_at.elapsed().as_secs()
+            );
+            if let Ok(stats) = self.process_manager.process_stats(&running.handle) {
+                if stats.process_count > 0 {
+                    status.push_str(&format!(
+                        " · CPU {:.0}% · {} · {} {}",
+                        stats.cpu_usage,
+                        format_bytes(stats.memory_bytes),
+                        stats.process_count,
+                        if stats.process_count == 1 {
+                            "process"
+                        } else {
+                            "processes"
+                        }
+                    ));
+                }
+            }
+            ui.label(
+                eframe::egui::RichText::new(status)
+                    .size(12.0)
+                    .color(eframe::egui::Color32::from_gray(120)),
+            );
+        }
+
+        if self.running.as_ref().is_some_and(|running| running.pty) {
+            ui.horizontal(|ui| {
+                let input = ui.add(
+                    eframe::egui::TextEdit::singleline(&mut self.input)
+                        .hint_text("Input for the command")
+                        .font(eframe::egui::TextStyle::Monospace)
+                        .desired_width(ui.available_width() - 120.0),
+                );
+                let enter_pressed =
+                    input.lost_focus() && ui.input(|i| i.key_pressed(eframe::egui::Key::Enter));
+                if ui.button("↵ Send").clicked() || enter_pressed {
+                    self.send_input();
+                    input.request_focus();
+                }
+            });
+        }
+
+        if !self.view.is_empty() {
+            self.view.show(ui);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    use ifm_ruta_core::services::ProcessManagerImpl;
+
+    /// Empty project directory with user settings of its own
+    fn project(name: &str) -> (PathBuf, SettingsManagerImpl) {
+        let dir = std::env::temp_dir().join(format!(
+            "ifm-ruta-command-panel-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let settings_manager = SettingsManagerImpl::with_path(dir.join("settings.toml"));
+        (dir, settings_manager)
+    }
+
+    fn open_panel(dir: &Path, settings_manager: SettingsManagerImpl) -> CommandPanel {
+        let process_manager = ProcessManagerImpl::with_settings(&settings_manager.get_settings());
+        CommandPanel::new(
+            dir.to_path_buf(),
+            Arc::new(process_manager),
+            settings_manager,
+        )
+    }
+
+    fn set_default_command(dir: &Path, command: &str) {
+        let settings = ProjectSettings {
+            default_command: Some(command.to_string()),
+            ..ProjectSettings::default()
+        };
+        SettingsManagerImpl::save_project_settings(dir, &settings).unwrap();
+    }
+
+    fn allow_auto_run(settings_manager: &mut SettingsManagerImpl, dir: &Path, command: &str) {
+        settings_manager
+            .update_settings(|settings| {
+                settings.security.auto_run_commands.push(AutoRunCommand {
+                    project_directory: dir.to_path_buf(),
+                    command: command.to_string(),
+                })
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn only_commands_the_user_allowed_run_on_open() {
+        let (dir, settings_manager) = project("not-allowed");
+        set_default_command(&dir, "echo hello");
+        let panel = open_panel(&dir, settings_manager);
+        assert!(panel.commands_executed().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(!panel.auto_run);
+
+        // Allowed for another command in the same project
+        let (dir, mut settings_manager) = project("other-command");
+        set_default_command(&dir, "echo hello");
+        allow_auto_run(&mut settings_manager, &dir, "echo goodbye");
+        let panel = open_panel(&dir, settings_manager);
+        assert!(panel.commands_executed().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // Allowed for the same command in another project
+        let (dir, mut settings_manager) = project("other-project");
+        set_default_command(&dir, "echo hello");
+        allow_auto_run(&mut settings_manager, &dir.join("other"), "echo hello");
+        let panel = open_panel(&dir, settings_manager);
+        assert!(panel.commands_executed().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let (dir, mut settings_manager) = project("allowed");
+        set_default_command(&dir, "echo hello");
+        allow_auto_run(&mut settings_manager, &dir, "echo hello");
+        let mut panel = open_panel(&dir, settings_manager);
+        assert_eq!(panel.commands_executed(), ["echo hello"]);
+        assert!(panel.auto_run);
+        panel.shutdown();
+        assert!(
+            panel.logs().starts_with("$ echo hello\n"),
+            "{}",
+            panel.logs()
+        );
+    }
+
+    #[test]
+    fn logs_keep_only_the_most_recent_lines() {
+        let (dir, settings_manager) = project("log-cap");
+        let mut panel = open_panel(&dir, settings_manager);
+        for line in 0..MAX_LINES + 10 {
+            panel.note(&format!("line {}", line));
+        }
+
+        let logs = panel.logs();
+        let mut lines = logs.lines();
+        assert_eq!(lines.next(), Some("[... 10 earlier lines dropped]"));
+        assert_eq!(lines.next(), Some("line 10"));
+        assert_eq!(
+            lines.last(),
+            Some(format!("line {}", MAX_LINES + 9).as_str())
+        );
+        assert_eq!(logs.lines().count(), MAX_LINES + 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn command_output_counts_towards_the_log_cap() {
+        let mut logs = LogTail::default();
+        logs.push("$ seq".to_string());
+        let mut output = LogTail::default();
+        for line in 0..MAX_LINES + 1 {
+            output.push(line.to_string());
+        }
+        logs.append(output);
+
+        let text = logs.text();
+        assert!(
+            text.starts_with("[... 2 earlier lines dropped]\n1\n"),
+            "{}",
+            &text[..40]
+        );
+        assert!(!text.contains("$ seq"));
+    }
+}
@@ -7,24 +7,43 @@
 use include_dir::{include_dir, Dir};
 use serde::Deserialize;
 use std::collections::VecDeque;
-use std::io::{self, BufRead};
+use std::io::Read;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use ifm_ruta_core::{
-    models::AppError,
+    models::{
+        check_feedback_protocol_version, AppError, FeedbackAttachment, FeedbackOption,
+        FeedbackOutcome, FeedbackRequest, FeedbackResult, FeedbackStatus, FeedbackStatusUpdate,
+        FormField, FormFieldType, FormSchema,
+    },
     services::{ConversationStorage, EventBusImpl, ProcessManagerImpl, SettingsManagerImpl},
-    utils::init_logging,
+    traits::SettingsManager,
+    utils::{init_logging, init_logging_with_layer},
 };
 
+/// Largest image that can be attached to a reply
+const MAX_ATTACHMENT_BYTES: usize = 10 * 1024 * 1024;
+
+/// Longest side of attachment thumbnails, in pixels
+const THUMBNAIL_SIZE: u32 = 96;
+
+/// Least time between two reports that the user is typing
+const TYPING_REPORT_INTERVAL: Duration = Duration::from_secs(2);
+
 // Include fonts directory
 static FONTS_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/fonts");
 
 // Re-export MCP modules from the mcp package
+mod command_panel;
 mod mcp;
+mod terminal_view;
 mod tools;
 
-use mcp::server::MCPRequest;
+use command_panel::CommandPanel;
+use mcp::logging::ClientLogger;
+use mcp::transport::serve_stdio;
 use mcp::MCPServer;
 use tools::InteractiveFeedbackTool;
 
@@ -71,9 +90,9 @@ impl ConversationManager {
             // Load all conversation sessions from storage
             match storage.get_project_sessions() {
                 Ok(sessions) => {
-                    println!("Loaded {} conversation sessions", sessions.len());
+                    tracing::debug!("Loaded {} conversation sessions", sessions.len());
                     for session in sessions {
-                        println!(
+                        tracing::debug!(
                             "Loading session: {} with {} messages",
                             session.session_id,
                             session.messages.len()
@@ -85,11 +104,11 @@ impl ConversationManager {
                     }
                 }
                 Err(e) => {
-                    println!("Error loading conversation sessions: {}", e);
+                    tracing::warn!("Error loading conversation sessions: {}", e);
                 }
             }
         } else {
-            println!("No storage available");
+            tracing::warn!("No storage available");
         }
     }
 
@@ -127,6 +146,74 @@ struct CursorContext {
     request_id: u64,
 }
 
+/// Editable state of one form field
+enum FormInput {
+    Text(String),
+    Toggle(bool),
+    Choice(Option<usize>),
+}
+
+impl FormInput {
+    /// Initial state from the field's default
+    fn new(field: &FormField) -> Self {
+        match (field.field_type, &field.enum_values) {
+            (FormFieldType::Boolean, _) => Self::Toggle(
+                field
+                    .default
+                    .as_ref()
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false),
+            ),
+            (FormFieldType::String, Some(values)) => Self::Choice(
+                field
+                    .default
+                    .as_ref()
+                    .and_then(|v| v.as_str())
+                    .and_then(|default| values.iter().position(|v| v == default)),
+            ),
+            _ => Self::Text(match &field.default {
+                Some(serde_json::Value::String(text)) => text.clone(),
+                Some(other) => other.to_string(),
+                None => String::new(),
+            }),
+        }
+    }
+
+    /// Convert to a JSON value; `None` when the field was left empty
+    fn to_value(&self, name: &str, field: &FormField) -> Result<Option<serde_json::Value>, String> {
+        match self {
+            Self::Toggle(checked) => Ok(Some(serde_json::Value::Bool(*checked))),
+            Self::Choice(index) => Ok(index
+                .and_then(|i| field.enum_values.as_ref()?.get(i).cloned())
+                .map(serde_json::Value::String)),
+            Self::Text(text) if text.trim().is_empty() => Ok(None),
+            Self::Text(text) => match field.field_type {
+                FormFieldType::Integer => text
+                    .trim()
+                    .parse::<i64>()
+                    .map(|n| Some(n.into()))
+                    .map_err(|_| format!("{} must be a whole number", field.label(name))),
+                FormFieldType::Number => text
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(serde_json::Number::from_f64)
+                    .map(|n| Some(serde_json::Value::Number(n)))
+                    .ok_or_else(|| format!("{} must be a number", field.label(name))),
+                _ => Ok(Some(serde_json::Value::String(text.clone()))),
+            },
+        }
+    }
+}
+
+/// Image attached in the composer, saved to the project on submit
+struct PendingAttachment {
+    name: String,
+    bytes: Vec<u8>,
+    mime_type: &'static str,
+    thumbnail: eframe::egui::TextureHandle,
+}
+
 /// Application state for GUI mode
 struct App {
     project_directory: String,
@@ -135,25 +222,83 @@ struct App {
     conversation_manager: ConversationManager,
     cursor_context: Option<CursorContext>,
     error_message: Option<String>,
+    options: Vec<FeedbackOption>,
+    allow_free_text: bool,
+    selected_option: Option<usize>,
+    form: Option<FormSchema>,
+    form_inputs: Vec<FormInput>,
+    attachments: Vec<PendingAttachment>,
+    command_panel: CommandPanel,
+    started_at: Instant,
+    deadline: Option<Instant>,
+    /// Status last written to stdout, and when
+    reported_status: Option<(FeedbackStatus, Instant)>,
+    result: Arc<Mutex<Option<FeedbackResult>>>,
 }
 
 impl App {
     fn new(
-        project_directory: String,
-        summary: String,
+        request: FeedbackRequest,
         cursor_context: Option<CursorContext>,
+        result: Arc<Mutex<Option<FeedbackResult>>>,
     ) -> Self {
         // Use real conversation storage
         let conversation_manager =
-            ConversationManager::new_with_storage(100, Path::new(&project_directory));
+            ConversationManager::new_with_storage(100, &request.project_directory);
+        let mut settings_manager = SettingsManagerImpl::new();
+        if let Err(e) = settings_manager.load_settings() {
+            tracing::warn!("Failed to load settings, using defaults: {}", e);
+        }
+        let settings = settings_manager.get_settings();
+        let command_panel = CommandPanel::new(
+            request.project_directory.clone(),
+            Arc::new(ProcessManagerImpl::with_settings(&settings)),
+            settings_manager,
+        );
+        let started_at = Instant::now();
+        let deadline = request.timeout().map(|timeout| started_at + timeout);
+        let selected_option = request.options.iter().position(|option| option.default);
+        let form_inputs = request
+            .form
+            .iter()
+            .flat_map(|form| form.properties.values().map(FormInput::new))
+            .collect();
 
         Self {
-            project_directory,
-            summary,
+            project_directory: request.project_directory.display().to_string(),
+            summary: request.prompt,
             feedback: String::new(),
             conversation_manager,
             cursor_context,
             error_message: None,
+            options: request.options,
+            allow_free_text: request.allow_free_text,
+            selected_option,
+            form: request.form,
+            form_inputs,
+            attachments: Vec::new(),
+            command_panel,
+            started_at,
+            deadline,
+            reported_status: None,
+            result,
+        }
+    }
+
+    /// Tell the server what the window is doing, skipping repeated typing reports
+    fn report_status(&mut self, status: FeedbackStatus) {
+        if let Some((reported, at)) = self.reported_status {
+            if reported == status
+                && (status != FeedbackStatus::Typing || at.elapsed() < TYPING_REPORT_INTERVAL)
+            {
+                return;
+            }
+        }
+        self.reported_status = Some((status, Instant::now()));
+
+        match serde_json::to_string(&FeedbackStatusUpdate::new(status)) {
+            Ok(line) => println!("{}", line),
+            Err(e) => tracing::warn!("Failed to encode status update: {}", e),
         }
     }
 
@@ -162,28 +307,396 @@ impl App {
             .add_conversation("user".to_string(), feedback);
     }
 
-    fn submit_feedback(&mut self) {
-        if self.feedback.trim().is_empty() {
-            self.error_message = Some("Please enter your feedback".to_string());
+    fn submit_feedback(&mut self, ctx: &eframe::egui::Context) {
+        let text = if self.allow_free_text {
+            self.feedback.clone()
+        } else {
+            String::new()
+        };
+        let selected_option = self
+            .selected_option
+            .and_then(|i| self.options.get(i))
+            .map(|option| option.label.clone());
+        let form_response = match self.collect_form_response() {
+            Ok(form_response) => form_response,
+            Err(message) => {
+                self.error_message = Some(message);
+                return;
+            }
+        };
+
+        // Images go along with a reply, but only count as one when text is allowed
+        if text.trim().is_empty()
+            && selected_option.is_none()
+            && form_response.is_none()
+            && (self.attachments.is_empty() || !self.allow_free_text)
+        {
+            let message = if self.options.is_empty() {
+                "Please enter your feedback"
+            } else if self.allow_free_text {
+                "Please pick an option or enter your feedback"
+            } else {
+                "Please pick an option"
+            };
+            self.error_message = Some(message.to_string());
             return;
         }
 
         // Add user feedback to conversation history
-        self.add_user_feedback(self.feedback.clone());
+        let mut history_entry = Vec::new();
+        if let Some(option) = &selected_option {
+            history_entry.push(format!("[{}]", option));
+        }
+        if let Some(form_response) = &form_response {
+            history_entry.push(serde_json::Value::Object(form_response.clone()).to_string());
+        }
+        if !text.trim().is_empty() {
+            history_entry.push(text.clone());
+        }
+        if !self.attachments.is_empty() {
+            history_entry.push(format!("📎 {} image(s) attached", self.attachments.len()));
+        }
 
-        // Output feedback to stdout for MCP to capture
-        println!("{}", self.feedback);
+        let attachments = match self.save_attachments() {
+            Ok(attachments) => attachments,
+            Err(e) => {
+                self.error_message = Some(format!("Failed to save attachments: {}", e));
+                return;
+            }
+        };
+        self.add_user_feedback(history_entry.join("\n"));
+
+        let mut result =
+            FeedbackResult::new(FeedbackOutcome::Submitted, text, self.started_at.elapsed());
+        result.selected_option = selected_option;
+        result.form_response = form_response;
+        result.attachments = attachments;
+        self.finish(ctx, result);
+    }
 
-        // Close application
-        std::process::exit(0);
+    /// Write attached images under the project's `.ifm-ruta/attachments/`
+    fn save_attachments(&self) -> Result<Vec<FeedbackAttachment>, AppError> {
+        let storage = ConversationStorage::new(Path::new(&self.project_directory));
+        self.attachments
+            .iter()
+            .map(|attachment| storage.save_attachment(&attachment.bytes, attachment.mime_type))
+            .collect()
     }
 
-    fn cancel_feedback(&mut self) {
-        // Output empty feedback
-        println!();
+    /// Attach a PNG or JPEG image, rejecting anything else
+    fn add_attachment(&mut self, ctx: &eframe::egui::Context, name: String, bytes: Vec<u8>) {
+        if bytes.len() > MAX_ATTACHMENT_BYTES {
+            self.error_message = Some(format!(
+                "{} is too large (limit is {} MB)",
+                name,
+                MAX_ATTACHMENT_BYTES / (1024 * 1024)
+            ));
+            return;
+        }
 
-        // Close application
-        std::process::exit(0);
+        let Some(mime_type) = FeedbackAttachment::sniff_image_mime_type(&bytes) else {
+            self.error_message = Some(format!(
+                "{}: only PNG and JPEG images can be attached",
+                name
+            ));
+            return;
+        };
+
+        let thumbnail = match image::load_from_memory(&bytes) {
+            Ok(image) => image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgba8(),
+            Err(e) => {
+                self.error_message = Some(format!("Could not read image {}: {}", name, e));
+                return;
+            }
+        };
+        let thumbnail = eframe::egui::ColorImage::from_rgba_unmultiplied(
+            [thumbnail.width() as usize, thumbnail.height() as usize],
+            thumbnail.as_raw(),
+        );
+        let thumbnail = ctx.load_texture(&name, thumbnail, eframe::egui::TextureOptions::LINEAR);
+
+        self.error_message = None;
+        self.attachments.push(PendingAttachment {
+            name,
+            bytes,
+            mime_type,
+            thumbnail,
+        });
+    }
+
+    /// Attach files dropped onto the window
+    fn handle_dropped_files(&mut self, ctx: &eframe::egui::Context) {
+        let dropped_files = ctx.input(|i| i.raw.dropped_files.clone());
+        for file in dropped_files {
+            let name = match (&file.path, file.name.is_empty()) {
+                (Some(path), true) => path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                _ => file.name.clone(),
+            };
+            let bytes = match (&file.bytes, &file.path) {
+                (Some(bytes), _) => Ok(bytes.to_vec()),
+                (None, Some(path)) => std::fs::read(path),
+                (None, None) => continue,
+            };
+            match bytes {
+                Ok(bytes) => self.add_attachment(ctx, name, bytes),
+                Err(e) => self.error_message = Some(format!("Could not read {}: {}", name, e)),
+            }
+        }
+    }
+
+    /// Attach the image on the clipboard, if there is one
+    ///
+    /// egui only turns clipboard text into paste events, so images are read
+    /// directly when the paste shortcut is released.
+    fn paste_image(&mut self, ctx: &eframe::egui::Context) {
+        let Ok(image) = arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_image())
+        else {
+            return;
+        };
+
+        let Some(image) = image::RgbaImage::from_raw(
+            image.width as u32,
+            image.height as u32,
+            image.bytes.into_owned(),
+        ) else {
+            return;
+        };
+        let mut png = Vec::new();
+        if let Err(e) = image.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        {
+            self.error_message = Some(format!("Could not attach pasted image: {}", e));
+            return;
+        }
+
+        let name = format!("pasted-image-{}.png", self.attachments.len() + 1);
+        self.add_attachment(ctx, name, png);
+    }
+
+    /// Attach images picked from disk
+    fn pick_attachments(&mut self, ctx: &eframe::egui::Context) {
+        let Some(paths) = rfd::FileDialog::new()
+            .add_filter("Images", &["png", "jpg", "jpeg"])
+            .pick_files()
+        else {
+            return;
+        };
+
+        for path in paths {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            match std::fs::read(&path) {
+                Ok(bytes) => self.add_attachment(ctx, name, bytes),
+                Err(e) => self.error_message = Some(format!("Could not read {}: {}", name, e)),
+            }
+        }
+    }
+
+    /// Render the attach button and thumbnails of attached images
+    fn render_attachments(&mut self, ui: &mut eframe::egui::Ui) {
+        let mut removed = None;
+
+        ui.horizontal_wrapped(|ui| {
+            if ui
+                .button("📎 Attach image...")
+                .on_hover_text("PNG or JPEG; you can also drop or paste images")
+                .clicked()
+            {
+                self.pick_attachments(ui.ctx());
+            }
+
+            for (i, attachment) in self.attachments.iter().enumerate() {
+                ui.vertical(|ui| {
+                    ui.add(eframe::egui::Image::new((
+                        attachment.thumbnail.id(),
+                        attachment.thumbnail.size_vec2(),
+                    )))
+                    .on_hover_text(&attachment.name);
+                    if ui.small_button("✕ Remove").clicked() {
+                        removed = Some(i);
+                    }
+                });
+            }
+        });
+
+        if let Some(i) = removed {
+            self.attachments.remove(i);
+        }
+    }
+
+    /// Build and validate the form response; `None` when there is no form
+    fn collect_form_response(
+        &self,
+    ) -> Result<Option<serde_json::Map<String, serde_json::Value>>, String> {
+        let Some(form) = &self.form else {
+            return Ok(None);
+        };
+
+        let mut response = serde_json::Map::new();
+        for ((name, field), input) in form.properties.iter().zip(&self.form_inputs) {
+            if let Some(value) = input.to_value(name, field)? {
+                response.insert(name.clone(), value);
+            }
+        }
+
+        form.validate_response(&response)
+            .map_err(|e| e.to_string())?;
+        Ok(Some(response))
+    }
+
+    /// Render the form as a two-column grid of labels and inputs
+    fn render_form(&mut self, ui: &mut eframe::egui::Ui) {
+        let Some(form) = &self.form else {
+            return;
+        };
+
+        eframe::egui::Grid::new("feedback_form")
+            .num_columns(2)
+            .spacing([12.0, 8.0])
+            .show(ui, |ui| {
+                for ((name, field), input) in form.properties.iter().zip(&mut self.form_inputs) {
+                    let label = if form.is_required(name) {
+                        format!("{} *", field.label(name))
+                    } else {
+                        field.label(name).to_string()
+                    };
+                    let mut response = ui.label(
+                        eframe::egui::RichText::new(label)
+                            .size(12.0)
+                            .color(eframe::egui::Color32::from_gray(180)),
+                    );
+                    if let Some(description) = &field.description {
+                        response = response.on_hover_text(description);
+                    }
+
+                    match input {
+                        FormInput::Text(text) => {
+                            ui.add(
+                                eframe::egui::TextEdit::singleline(text)
+                                    .desired_width(ui.available_width()),
+                            );
+                        }
+                        FormInput::Toggle(checked) => {
+                            ui.checkbox(checked, "");
+                        }
+                        FormInput::Choice(selected) => {
+                            let selected_text = selected
+                                .and_then(|i| field.enum_label(i))
+                                .unwrap_or("Select...")
+                                .to_string();
+                            let count = field.enum_values.as_ref().map_or(0, Vec::len);
+                            eframe::egui::ComboBox::from_id_source(name)
+                                .selected_text(selected_text)
+                                .show_ui(ui, |ui| {
+                                    for i in 0..count {
+                                        let option_label = field.enum_label(i).unwrap_or_default();
+                                        ui.selectable_value(selected, Some(i), option_label);
+                                    }
+                                });
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn cancel_feedback(&mut self, ctx: &eframe::egui::Context) {
+        let result = FeedbackResult::new(
+            FeedbackOutcome::Cancelled,
+            String::new(),
+            self.started_at.elapsed(),
+        );
+        self.finish(ctx, result);
+    }
+
+    /// Select an option, or clear the selection if it is already selected
+    fn toggle_option(&mut self, index: usize) {
+        self.selected_option = if self.selected_option == Some(index) {
+            None
+        } else {
+            Some(index)
+        };
+        self.error_message = None;
+    }
+
+    /// Render quick-reply options as toggle buttons numbered for keyboard selection
+    fn render_options(&mut self, ui: &mut eframe::egui::Ui) {
+        let mut clicked = None;
+
+        ui.horizontal_wrapped(|ui| {
+            for (i, option) in self.options.iter().enumerate() {
+                let selected = self.selected_option == Some(i);
+                let text = if i < 9 {
+                    format!("{}  {}", i + 1, option.label)
+                } else {
+                    option.label.clone()
+                };
+
+                let button = eframe::egui::Button::new(
+                    eframe::egui::RichText::new(text)
+                        .size(13.0)
+                        .color(eframe::egui::Color32::WHITE),
+                )
+                .fill(if selected {
+                    eframe::egui::Color32::from_rgb(50, 100, 200)
+                } else {
+                    eframe::egui::Color32::from_gray(90)
+                })
+                .min_size(eframe::egui::Vec2::new(0.0, 30.0));
+
+                let mut response = ui.add(button);
+                if let Some(description) = &option.description {
+                    response = response.on_hover_text(description);
+                }
+                if response.clicked() {
+                    clicked = Some(i);
+                }
+            }
+        });
+
+        if let Some(i) = clicked {
+            self.toggle_option(i);
+        }
+
+        // Show the description of the current choice
+        if let Some(description) = self
+            .selected_option
+            .and_then(|i| self.options.get(i))
+            .and_then(|option| option.description.as_ref())
+        {
+            ui.label(
+                eframe::egui::RichText::new(description)
+                    .size(11.0)
+                    .color(eframe::egui::Color32::from_gray(150)),
+            );
+        }
+    }
+
+    /// Time left before the window closes on its own
+    fn remaining_time(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Record the result and close the window; it is reported once the event loop exits
+    fn finish(&mut self, ctx: &eframe::egui::Context, result: FeedbackResult) {
+        self.record_result(result);
+        ctx.send_viewport_cmd(eframe::egui::ViewportCommand::Close);
+    }
+
+    /// Keep the first result, with the output of any commands that were run
+    fn record_result(&mut self, mut result: FeedbackResult) {
+        self.command_panel.shutdown();
+        result.command_logs = self.command_panel.logs();
+        result.commands_executed = self.command_panel.commands_executed().to_vec();
+
+        if let Ok(mut slot) = self.result.lock() {
+            slot.get_or_insert(result);
+        }
     }
 
     fn render_richtext_content(&self, ui: &mut eframe::egui::Ui, content: &str) {
@@ -286,7 +799,35 @@ impl App {
 }
 
 impl eframe::App for App {
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // Closing the window without answering still reports the commands that ran
+        let result = FeedbackResult::new(
+            FeedbackOutcome::Cancelled,
+            String::new(),
+            self.started_at.elapsed(),
+        );
+        self.record_result(result);
+    }
+
     fn update(&mut self, ctx: &eframe::egui::Context, _frame: &mut eframe::Frame) {
+        if self.reported_status.is_none() {
+            self.report_status(FeedbackStatus::Opened);
+        }
+
+        // Give up waiting once the timeout expires; keep repainting for the countdown
+        if let Some(remaining) = self.remaining_time() {
+            if remaining.is_zero() {
+                let result = FeedbackResult::new(
+                    FeedbackOutcome::Timeout,
+                    String::new(),
+                    self.started_at.elapsed(),
+                );
+                self.finish(ctx, result);
+            } else {
+                ctx.request_repaint_after(Duration::from_millis(250));
+            }
+        }
+
         // Left panel - Conversation history
         eframe::egui::SidePanel::left("conversation_panel")
             .resizable(true)
@@ -387,7 +928,24 @@ impl eframe::App for App {
         eframe::egui::CentralPanel::default().show(ctx, |ui| {
             // Use vertical layout with fixed height for main content
             ui.vertical(|ui| {
-                ui.heading("Interactive Feedback MCP");
+                ui.horizontal(|ui| {
+                    ui.heading("Interactive Feedback MCP");
+
+                    // Countdown until the default reply is sent
+                    if let Some(remaining) = self.remaining_time() {
+                        ui.with_layout(eframe::egui::Layout::right_to_left(eframe::egui::Align::Center), |ui| {
+                            let secs = remaining.as_secs();
+                            let color = if secs < 30 {
+                                eframe::egui::Color32::from_rgb(255, 100, 100)
+                            } else {
+                                eframe::egui::Color32::from_gray(120)
+                            };
+                            ui.label(eframe::egui::RichText::new(format!("⏱ Auto-reply in {}:{:02}", secs / 60, secs % 60))
+                                .size(12.0)
+                                .color(color));
+                        });
+                    }
+                });
                 ui.add_space(10.0);
 
                 // Scrollable content area with fixed height
@@ -509,6 +1067,10 @@ impl eframe::App for App {
                             ui.add_space(10.0);
                         }
 
+                        // Command section
+                        self.command_panel.show(ui);
+                        ui.add_space(10.0);
+
                         // Feedback input section with improved styling
                         let feedback_frame = eframe::egui::Frame::group(ui.style())
                             .fill(eframe::egui::Color32::from_rgba_premultiplied(20, 20, 20, 200))
@@ -537,32 +1099,54 @@ impl eframe::App for App {
 
                                 ui.add_space(12.0);
 
-                                // Text input with better styling
-                                let text_edit = eframe::egui::TextEdit::multiline(&mut self.feedback)
-                                    .hint_text("Enter your feedback here...\n\nSupports multiline text and markdown formatting:\n• **bold text**\n• *italic text*\n• ```code blocks```\n\nUse Ctrl+Enter to submit.")
-                                    .font(eframe::egui::TextStyle::Body)
-                                    .desired_width(ui.available_width())
-                                    .desired_rows(8);
+                                // Quick-reply options above the text box
+                                if !self.options.is_empty() {
+                                    self.render_options(ui);
+                                    ui.add_space(12.0);
+                                }
 
-                                ui.add_sized(
-                                    [ui.available_width(), 200.0],
-                                    text_edit
-                                );
+                                // Structured form fields
+                                if self.form.is_some() {
+                                    self.render_form(ui);
+                                    ui.add_space(12.0);
+                                }
 
-                                ui.add_space(8.0);
+                                // Text input with better styling
+                                if self.allow_free_text {
+                                    let text_edit = eframe::egui::TextEdit::multiline(&mut self.feedback)
+                                        .hint_text("Enter your feedback here...\n\nSupports multiline text and markdown formatting:\n• **bold text**\n• *italic text*\n• ```code blocks```\n\nUse Ctrl+Enter to submit.")
+                                        .font(eframe::egui::TextStyle::Body)
+                                        .desired_width(ui.available_width())
+                                        .desired_rows(8);
+
+                                    let response = ui.add_sized(
+                                        [ui.available_width(), 200.0],
+                                        text_edit
+                                    );
+                                    if response.changed() {
+                                        self.report_status(FeedbackStatus::Typing);
+                                    }
 
-                                // Character count and tips
-                                ui.horizontal(|ui| {
-                                    ui.label(eframe::egui::RichText::new(format!("Characters: {}", self.feedback.len()))
-                                        .size(10.0)
-                                        .color(eframe::egui::Color32::from_gray(120)));
+                                    ui.add_space(8.0);
 
-                                    ui.with_layout(eframe::egui::Layout::right_to_left(eframe::egui::Align::Center), |ui| {
-                                        ui.label(eframe::egui::RichText::new("💡 Tip: Use markdown for better formatting")
+                                    // Character count and tips
+                                    ui.horizontal(|ui| {
+                                        ui.label(eframe::egui::RichText::new(format!("Characters: {}", self.feedback.len()))
                                             .size(10.0)
                                             .color(eframe::egui::Color32::from_gray(120)));
+
+                                        ui.with_layout(eframe::egui::Layout::right_to_left(eframe::egui::Align::Center), |ui| {
+                                            ui.label(eframe::egui::RichText::new("💡 Tip: Use markdown for better formatting")
+                                                .size(10.0)
+                                                .color(eframe::egui::Color32::from_gray(120)));
+                                        });
                                     });
-                                });
+
+                                    ui.add_space(8.0);
+                                }
+
+                                // Attached images, also allowed when only options or a form can be used
+                                self.render_attachments(ui);
                             });
                         });
                     });
@@ -600,7 +1184,7 @@ impl eframe::App for App {
                         .min_size(eframe::egui::Vec2::new(150.0, 35.0));
 
                     if ui.add(submit_button).clicked() {
-                        self.submit_feedback();
+                        self.submit_feedback(ctx);
                     }
 
                     ui.add_space(10.0);
@@ -613,7 +1197,7 @@ impl eframe::App for App {
                         .min_size(eframe::egui::Vec2::new(100.0, 35.0));
 
                     if ui.add(cancel_button).clicked() {
-                        self.cancel_feedback();
+                        self.cancel_feedback(ctx);
                     }
 
                     // Spacer to push buttons to the left
@@ -627,11 +1211,51 @@ impl eframe::App for App {
 
             // Keyboard shortcuts
             if ctx.input(|i| i.key_pressed(eframe::egui::Key::Enter) && i.modifiers.ctrl) {
-                self.submit_feedback();
+                self.submit_feedback(ctx);
             }
 
             if ctx.input(|i| i.key_pressed(eframe::egui::Key::Escape)) {
-                self.cancel_feedback();
+                self.cancel_feedback(ctx);
+            }
+
+            // Images can be dropped or pasted anywhere in the window
+            self.handle_dropped_files(ctx);
+
+            let paste_released = ctx.input(|i| {
+                i.events.iter().any(|event| {
+                    matches!(
+                        event,
+                        eframe::egui::Event::Key {
+                            key: eframe::egui::Key::V,
+                            pressed: false,
+                            modifiers,
+                            ..
+                        } if modifiers.command
+                    )
+                })
+            });
+            if paste_released {
+                self.paste_image(ctx);
+            }
+
+            // Number keys pick an option while no text field has focus
+            if !self.options.is_empty() && ctx.memory(|m| m.focused().is_none()) {
+                const OPTION_KEYS: [eframe::egui::Key; 9] = [
+                    eframe::egui::Key::Num1,
+                    eframe::egui::Key::Num2,
+                    eframe::egui::Key::Num3,
+                    eframe::egui::Key::Num4,
+                    eframe::egui::Key::Num5,
+                    eframe::egui::Key::Num6,
+                    eframe::egui::Key::Num7,
+                    eframe::egui::Key::Num8,
+                    eframe::egui::Key::Num9,
+                ];
+                for (i, key) in OPTION_KEYS.iter().enumerate().take(self.options.len()) {
+                    if ctx.input(|input| input.key_pressed(*key) && input.modifiers.is_none()) {
+                        self.toggle_option(i);
+                    }
+                }
             }
         });
     }
@@ -639,55 +1263,57 @@ impl eframe::App for App {
 
 /// Run the MCP server
 fn run_mcp_server() -> Result<(), AppError> {
-    // Initialize logging
-    init_logging(tracing::Level::INFO)?;
+    // Initialize logging, to stderr and to the client once it connects
+    let client_logger = ClientLogger::default();
+    init_logging_with_layer(tracing::Level::INFO, client_logger.layer())?;
 
     // Initialize core services
-    let settings_manager = std::sync::Arc::new(SettingsManagerImpl::new());
-    let process_manager = std::sync::Arc::new(ProcessManagerImpl::new());
-    #[allow(clippy::arc_with_non_send_sync)]
-    let event_bus = std::sync::Arc::new(EventBusImpl::new());
+    let mut settings_manager = SettingsManagerImpl::new();
+    if let Err(e) = settings_manager.load_settings() {
+        tracing::warn!("Failed to load settings, using defaults: {}", e);
+    }
+    let settings = settings_manager.get_settings();
+    let settings_manager = Arc::new(settings_manager);
+    let event_bus = Arc::new(EventBusImpl::new());
+    let process_manager =
+        Arc::new(ProcessManagerImpl::with_settings(&settings).with_event_bus(event_bus.clone()));
 
     // Create MCP server
-    let mut server = MCPServer::new(settings_manager, process_manager, event_bus);
+    let mut server = MCPServer::new(settings_manager.clone(), process_manager, event_bus)
+        .with_client_logger(client_logger);
 
     // Register tools
     server.register_tool(Box::new(InteractiveFeedbackTool::new()));
 
-    // Run the server with stdin/stdout like Go
-    let stdin = io::stdin();
-    let lines = stdin.lock().lines();
-
-    for line in lines {
-        let line = line?;
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-
-        // Parse JSON request like Go
-        let request: MCPRequest = serde_json::from_str(line)?;
-
-        // Handle request and send response like Go
-        if let Some(response) = server.handle_request(request)? {
-            println!("{}", serde_json::to_string(&response)?);
-        }
-        // Notifications don't get responses (per JSON-RPC 2.0 spec)
-    }
-
-    Ok(())
+    // Serve stdin/stdout on a multi-threaded runtime so tool calls run concurrently
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(serve_stdio(Arc::new(server)))
 }
 
 /// Run the GUI application
-fn run_gui_app(project_directory: String, summary: String, cursor_context: Option<CursorContext>) {
-    println!("egui GUI started with project: {}", project_directory);
-    println!("egui GUI started with summary: {}", summary);
+///
+/// Status envelopes are written to stdout as JSON lines while the window is
+/// open, and the result envelope as a single JSON line once it closes;
+/// closing the window without answering counts as cancelled.
+fn run_gui_app(
+    request: FeedbackRequest,
+    cursor_context: Option<CursorContext>,
+) -> Result<(), AppError> {
+    // Initialize logging; stdout is reserved for the feedback result
+    init_logging(tracing::Level::WARN)?;
 
-    // Initialize logging
-    env_logger::init();
+    tracing::info!(
+        "egui GUI started with project: {}",
+        request.project_directory.display()
+    );
+    tracing::info!("egui GUI started with summary: {}", request.prompt);
 
     // Create app
-    let app = App::new(project_directory, summary, cursor_context);
+    let started_at = Instant::now();
+    let result = Arc::new(Mutex::new(None));
+    let app = App::new(request, cursor_context, Arc::clone(&result));
 
     // Run the GUI
     let options = eframe::NativeOptions {
@@ -699,7 +1325,7 @@ fn run_gui_app(project_directory: String, summary: String, cursor_context: Optio
         ..Default::default()
     };
 
-    let _ = eframe::run_native(
+    eframe::run_native(
         "Interactive Feedback MCP",
         options,
         Box::new(|cc| {
@@ -754,7 +1380,32 @@ fn run_gui_app(project_directory: String, summary: String, cursor_context: Optio
 
             Ok(Box::new(app))
         }),
-    );
+    )
+    .map_err(|e| AppError::InternalError(anyhow::anyhow!("Failed to run GUI: {}", e)))?;
+
+    let result = result
+        .lock()
+        .ok()
+        .and_then(|mut result| result.take())
+        .unwrap_or_else(|| {
+            FeedbackResult::new(
+                FeedbackOutcome::Cancelled,
+                String::new(),
+                started_at.elapsed(),
+            )
+        });
+    println!("{}", serde_json::to_string(&result)?);
+
+    Ok(())
+}
+
+/// Read the feedback request envelope from stdin
+fn read_feedback_request() -> Result<FeedbackRequest, AppError> {
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+    let request: FeedbackRequest = serde_json::from_str(&input)?;
+    check_feedback_protocol_version(request.version)?;
+    Ok(request)
 }
 
 fn main() -> Result<(), AppError> {
@@ -768,6 +1419,12 @@ fn main() -> Result<(), AppError> {
         return Ok(());
     }
 
+    // Check if running as the feedback window for the MCP server
+    if args.len() > 1 && args[1] == "--feedback-request" {
+        let request = read_feedback_request()?;
+        return run_gui_app(request, None);
+    }
+
     // Check if running as GUI with arguments
     if args.len() > 1 {
         let project_directory = args[1].clone();
@@ -778,8 +1435,8 @@ fn main() -> Result<(), AppError> {
         };
 
         // Run as GUI application
-        run_gui_app(project_directory, summary, None);
-        return Ok(());
+        let request = FeedbackRequest::new(project_directory.into(), summary);
+        return run_gui_app(request, None);
     }
 
     // Default: show help
@@ -793,6 +1450,10 @@ fn main() -> Result<(), AppError> {
         "  {} <project_dir> [summary]         # Run as GUI application",
         args[0]
     );
+    println!(
+        "  {} --feedback-request              # Run as GUI reading a request from stdin",
+        args[0]
+    );
     println!(
         "  {}                                 # Show this help",
         args[0]
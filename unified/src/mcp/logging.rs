@@ -0,0 +1,254 @@
+//! Log messages for the client
+//!
+//! A `tracing` layer forwards events at or above the level the client chose
+//! with `logging/setLevel` as `notifications/message`, so they show up in the
+//! client's log view. The event's fields become the structured `data`.
+//!
+//! The layer's level is a reloadable filter, so events below it are never
+//! even built. Messages logged before the client sends
+//! `notifications/initialized` are held back and sent once it does.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+
+use serde_json::{json, Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::{Filtered, LevelFilter};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::{reload, Registry};
+
+use super::peer::Peer;
+
+/// Syslog severities used by MCP, from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+impl LogLevel {
+    const ALL: [LogLevel; 8] = [
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Notice,
+        LogLevel::Warning,
+        LogLevel::Error,
+        LogLevel::Critical,
+        LogLevel::Alert,
+        LogLevel::Emergency,
+    ];
+
+    /// Parse the name used on the wire
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|level| level.as_str() == name)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Notice => "notice",
+            LogLevel::Warning => "warning",
+            LogLevel::Error => "error",
+            LogLevel::Critical => "critical",
+            LogLevel::Alert => "alert",
+            LogLevel::Emergency => "emergency",
+        }
+    }
+
+    fn from_tracing(level: &Level) -> Self {
+        match *level {
+            Level::ERROR => LogLevel::Error,
+            Level::WARN => LogLevel::Warning,
+            Level::INFO => LogLevel::Info,
+            Level::DEBUG | Level::TRACE => LogLevel::Debug,
+        }
+    }
+
+    /// Least severe `tracing` events that map to this level or above
+    fn to_tracing(self) -> LevelFilter {
+        match self {
+            LogLevel::Debug => LevelFilter::TRACE,
+            LogLevel::Info | LogLevel::Notice => LevelFilter::INFO,
+            LogLevel::Warning => LevelFilter::WARN,
+            LogLevel::Error => LevelFilter::ERROR,
+            // `tracing` has nothing more severe than errors
+            LogLevel::Critical | LogLevel::Alert | LogLevel::Emergency => LevelFilter::OFF,
+        }
+    }
+}
+
+/// Least severe level sent until the client chooses one
+const DEFAULT_LEVEL: LogLevel = LogLevel::Warning;
+
+/// Most messages held back until the client is initialized; older ones are dropped
+const MAX_HELD_MESSAGES: usize = 100;
+
+/// `tracing` layer installed by [`ClientLogger::layer`]
+pub type FilteredClientLogLayer =
+    Filtered<ClientLogLayer, reload::Layer<LevelFilter, Registry>, Registry>;
+
+struct LoggerState {
+    /// Index into `LogLevel::ALL`
+    level: AtomicU8,
+    /// Changes the level of the installed layer
+    filter: OnceLock<reload::Handle<LevelFilter, Registry>>,
+    /// Connection to log to; messages are dropped while there is none
+    peer: RwLock<Option<Peer>>,
+    /// Messages waiting for the client to finish initializing, `None` once it has
+    held: Mutex<Option<VecDeque<Value>>>,
+}
+
+/// Sends log messages to the connected client
+///
+/// Clones share the level and connection, so the server can hold one while
+/// the [`ClientLogLayer`] made from another is installed in `tracing`.
+#[derive(Clone)]
+pub struct ClientLogger {
+    state: Arc<LoggerState>,
+}
+
+impl Default for ClientLogger {
+    fn default() -> Self {
+        Self {
+            state: Arc::new(LoggerState {
+                level: AtomicU8::new(DEFAULT_LEVEL as u8),
+                filter: OnceLock::new(),
+                peer: RwLock::new(None),
+                held: Mutex::new(Some(VecDeque::new())),
+            }),
+        }
+    }
+}
+
+impl ClientLogger {
+    /// `tracing` layer that logs through this logger, filtered to its level
+    pub fn layer(&self) -> FilteredClientLogLayer {
+        let (filter, handle) = reload::Layer::new(self.level().to_tracing());
+        if self.state.filter.set(handle).is_err() {
+            tracing::warn!("Client log layer created twice; only the first follows the level");
+        }
+        ClientLogLayer {
+            logger: self.clone(),
+        }
+        .with_filter(filter)
+    }
+
+    /// Least severe level to send
+    pub fn level(&self) -> LogLevel {
+        LogLevel::ALL[usize::from(self.state.level.load(Ordering::Relaxed))]
+    }
+
+    /// Handle `logging/setLevel`
+    pub fn set_level(&self, level: LogLevel) {
+        self.state.level.store(level as u8, Ordering::Relaxed);
+        if let Some(filter) = self.state.filter.get() {
+            if let Err(e) = filter.reload(level.to_tracing()) {
+                tracing::warn!("Failed to change the client log level: {}", e);
+            }
+        }
+    }
+
+    /// Start sending messages to `peer`
+    pub fn connect(&self, peer: &Peer) {
+        *self.state.peer.write().unwrap() = Some(peer.clone());
+    }
+
+    /// Stop sending messages, releasing the peer
+    pub fn disconnect(&self) {
+        *self.state.peer.write().unwrap() = None;
+    }
+
+    /// Handle `notifications/initialized`, sending the messages held back so far
+    pub fn initialized(&self) {
+        let mut held = self.state.held.lock().unwrap();
+        // Send while holding the lock so newer messages cannot overtake these
+        for message in held.take().unwrap_or_default() {
+            self.send(message);
+        }
+    }
+
+    fn log(&self, level: LogLevel, logger: &str, data: Value) {
+        if level < self.level() {
+            return;
+        }
+        let message = json!({
+            "level": level.as_str(),
+            "logger": logger,
+            "data": data
+        });
+
+        if let Some(held) = self.state.held.lock().unwrap().as_mut() {
+            if held.len() == MAX_HELD_MESSAGES {
+                held.pop_front();
+            }
+            held.push_back(message);
+            return;
+        }
+        self.send(message);
+    }
+
+    fn send(&self, message: Value) {
+        let peer = self.state.peer.read().unwrap().clone();
+        if let Some(peer) = peer {
+            // Failing to log is not worth logging
+            let _ = peer.notify("notifications/message", message);
+        }
+    }
+}
+
+/// `tracing` layer forwarding events to a [`ClientLogger`]
+pub struct ClientLogLayer {
+    logger: ClientLogger,
+}
+
+impl<S: Subscriber> Layer<S> for ClientLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let level = LogLevel::from_tracing(metadata.level());
+        let mut fields = FieldCollector::default();
+        event.record(&mut fields);
+        self.logger
+            .log(level, metadata.target(), Value::Object(fields.0));
+    }
+}
+
+/// Collects an event's fields, including its `message`, as JSON
+#[derive(Default)]
+struct FieldCollector(Map<String, Value>);
+
+impl Visit for FieldCollector {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), json!(format!("{:?}", value)));
+    }
+}
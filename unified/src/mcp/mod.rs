@@ -1,7 +1,16 @@
 //! MCP protocol implementation
 
+pub mod logging;
+pub mod peer;
+pub mod prompts;
 pub mod protocol;
+pub mod registry;
+pub mod resources;
+pub mod roots;
 pub mod server;
+pub mod subscriptions;
+pub mod transport;
+pub mod uri;
 
 // Re-export
 pub use server::*;
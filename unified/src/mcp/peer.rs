@@ -0,0 +1,132 @@
+//! Messages from the server to the client
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio::sync::oneshot;
+
+use ifm_ruta_core::models::AppError;
+use ifm_ruta_core::traits::ProgressSink;
+
+use super::transport::ProtocolWriter;
+
+/// Longest the client gets to answer a request from the server
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Result or error object of a response
+type ResponseOutcome = Result<Value, Value>;
+
+/// The client end of the connection
+///
+/// Sends requests and notifications to the client, and hands each response
+/// the transport receives to the request waiting for it.
+#[derive(Clone)]
+pub struct Peer {
+    writer: ProtocolWriter,
+    pending: Arc<Mutex<HashMap<String, oneshot::Sender<ResponseOutcome>>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl Peer {
+    pub fn new(writer: ProtocolWriter) -> Self {
+        Self {
+            writer,
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+        }
+    }
+
+    /// Send a notification
+    pub fn notify(&self, method: &str, params: Value) -> Result<(), AppError> {
+        self.writer.send(&json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params
+        }))
+    }
+
+    /// Send a request and wait for the client's result
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value, AppError> {
+        let id = json!(format!(
+            "ifm-ruta-{}",
+            self.next_id.fetch_add(1, Ordering::Relaxed)
+        ));
+        let key = Self::key(&id);
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock().unwrap().insert(key.clone(), sender);
+
+        let sent = self.writer.send(&json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params
+        }));
+        if let Err(e) = sent {
+            self.pending.lock().unwrap().remove(&key);
+            return Err(e);
+        }
+
+        let failed = |message: String| AppError::InternalError(anyhow::anyhow!(message));
+        match tokio::time::timeout(REQUEST_TIMEOUT, receiver).await {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(error))) => Err(failed(format!("Client rejected {}: {}", method, error))),
+            Ok(Err(_)) => Err(failed(format!("Connection closed during {}", method))),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&key);
+                Err(failed(format!("Client did not answer {} in time", method)))
+            }
+        }
+    }
+
+    /// Deliver a response from the client, returning whether a request was waiting for it
+    pub fn handle_response(&self, id: &Value, outcome: ResponseOutcome) -> bool {
+        let sender = self.pending.lock().unwrap().remove(&Self::key(id));
+        match sender {
+            // The request may have timed out meanwhile; that is not an error here
+            Some(sender) => {
+                let _ = sender.send(outcome);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Ids may be numbers or strings; their JSON text keeps `1` and `"1"` apart
+    fn key(id: &Value) -> String {
+        id.to_string()
+    }
+}
+
+/// Sends a tool's progress to the client as `notifications/progress`
+pub struct ProgressNotifier {
+    peer: Peer,
+    token: Value,
+}
+
+impl ProgressNotifier {
+    /// Notifier for the request that sent `token` as its `progressToken`
+    pub fn new(peer: Peer, token: Value) -> Self {
+        Self { peer, token }
+    }
+}
+
+impl ProgressSink for ProgressNotifier {
+    fn report(&self, progress: f64, total: Option<f64>, message: Option<&str>) {
+        let mut params = json!({
+            "progressToken": self.token,
+            "progress": progress
+        });
+        if let Some(total) = total {
+            params["total"] = json!(total);
+        }
+        if let Some(message) = message {
+            params["message"] = json!(message);
+        }
+        if let Err(e) = self.peer.notify("notifications/progress", params) {
+            tracing::debug!("Failed to send progress: {}", e);
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Feedback workflow prompts
+//!
+//! Prompt templates are markdown files named after the prompt, with an
+//! optional YAML frontmatter giving a `description` and the `arguments` the
+//! body refers to as `{{name}}`. The built-in templates are overridden by
+//! those in `~/.config/ifm-ruta/prompts/`, which are in turn overridden by
+//! the `.ifm-ruta/prompts/` directories of known projects, so teams can
+//! ship their workflows with the repository.
+
+use std::path::{Path, PathBuf};
+
+use include_dir::{include_dir, Dir};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+/// Templates built into the binary
+static BUILTIN_PROMPTS: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/prompts");
+
+/// Prompt request failure
+#[derive(Debug, thiserror::Error)]
+pub enum PromptError {
+    #[error("Unknown prompt: {name}")]
+    NotFound { name: String },
+
+    #[error("Missing required argument `{argument}` for prompt {name}")]
+    MissingArgument { name: String, argument: String },
+}
+
+/// Argument of a prompt template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptArgument {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    /// Value used when the client leaves the argument out
+    #[serde(default, skip_serializing)]
+    pub default: Option<String>,
+}
+
+/// Frontmatter of a template file
+#[derive(Debug, Default, Deserialize)]
+struct Frontmatter {
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    arguments: Vec<PromptArgument>,
+}
+
+/// A parameterized prompt
+#[derive(Debug, Clone)]
+pub struct PromptTemplate {
+    pub name: String,
+    pub description: Option<String>,
+    pub arguments: Vec<PromptArgument>,
+    pub body: String,
+}
+
+impl PromptTemplate {
+    /// Parse a template file's content
+    pub fn parse(name: &str, content: &str) -> Result<Self, serde_yaml::Error> {
+        let (frontmatter, body) = match split_frontmatter(content) {
+            Some((frontmatter, body)) => (serde_yaml::from_str(frontmatter)?, body),
+            None => (Frontmatter::default(), content),
+        };
+        Ok(Self {
+            name: name.to_string(),
+            description: frontmatter.description,
+            arguments: frontmatter.arguments,
+            body: body.trim().to_string(),
+        })
+    }
+
+    /// Entry of `prompts/list`
+    pub fn to_listing(&self) -> Value {
+        let mut listing = json!({
+            "name": self.name,
+            "arguments": self.arguments
+        });
+        if let Some(description) = &self.description {
+            listing["description"] = json!(description);
+        }
+        listing
+    }
+
+    /// Body with each `{{argument}}` replaced by its value
+    pub fn render(&self, values: &Map<String, Value>) -> Result<String, PromptError> {
+        let mut text = self.body.clone();
+        for argument in &self.arguments {
+            let value = match values.get(&argument.name) {
+                Some(Value::String(value)) => value.clone(),
+                Some(value) => value.to_string(),
+                None if argument.required => {
+                    return Err(PromptError::MissingArgument {
+                        name: self.name.clone(),
+                        argument: argument.name.clone(),
+                    })
+                }
+                None => argument.default.clone().unwrap_or_default(),
+            };
+            text = text.replace(&format!("{{{{{}}}}}", argument.name), &value);
+        }
+        Ok(text)
+    }
+}
+
+/// Frontmatter and body of a file starting with a `---` fence
+fn split_frontmatter(content: &str) -> Option<(&str, &str)> {
+    let rest = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Directory of the user's own templates
+fn user_prompts_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("ifm-ruta").join("prompts"))
+}
+
+/// Templates from every source, later sources replacing earlier ones of the same name
+pub fn load_prompts(project_directories: &[PathBuf]) -> Vec<PromptTemplate> {
+    let mut prompts: Vec<PromptTemplate> = Vec::new();
+    let mut add =
+        |prompt: PromptTemplate| match prompts.iter_mut().find(|known| known.name == prompt.name) {
+            Some(known) => *known = prompt,
+            None => prompts.push(prompt),
+        };
+
+    for file in BUILTIN_PROMPTS.files() {
+        let (Some(name), Some(content)) = (template_name(file.path()), file.contents_utf8()) else {
+            continue;
+        };
+        match PromptTemplate::parse(&name, content) {
+            Ok(prompt) => add(prompt),
+            Err(e) => tracing::error!("Invalid built-in prompt {}: {}", name, e),
+        }
+    }
+
+    let directories = user_prompts_dir().into_iter().chain(
+        project_directories
+            .iter()
+            .map(|project| project.join(".ifm-ruta").join("prompts")),
+    );
+    for directory in directories {
+        for prompt in load_directory(&directory) {
+            add(prompt);
+        }
+    }
+
+    prompts.sort_by(|a, b| a.name.cmp(&b.name));
+    prompts
+}
+
+/// Templates in a directory, skipping files that cannot be read or parsed
+fn load_directory(directory: &Path) -> Vec<PromptTemplate> {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    paths.sort();
+
+    let mut prompts = Vec::new();
+    for path in paths {
+        let Some(name) = template_name(&path) else {
+            continue;
+        };
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| PromptTemplate::parse(&name, &content).map_err(|e| e.to_string()));
+        match parsed {
+            Ok(prompt) => prompts.push(prompt),
+            Err(e) => tracing::warn!("Skipping prompt {}: {}", path.display(), e),
+        }
+    }
+    prompts
+}
+
+/// Prompt name of a `.md` template file
+fn template_name(path: &Path) -> Option<String> {
+    if path.extension()? != "md" {
+        return None;
+    }
+    Some(path.file_stem()?.to_string_lossy().into_owned())
+}
+
+/// Result of `prompts/get`
+pub fn get_prompt(
+    prompts: &[PromptTemplate],
+    name: &str,
+    arguments: &Map<String, Value>,
+) -> Result<Value, PromptError> {
+    let prompt = prompts
+        .iter()
+        .find(|prompt| prompt.name == name)
+        .ok_or_else(|| PromptError::NotFound {
+            name: name.to_string(),
+        })?;
+    let text = prompt.render(arguments)?;
+
+    let mut result = json!({
+        "messages": [{
+            "role": "user",
+            "content": { "type": "text", "text": text }
+        }]
+    });
+    if let Some(description) = &prompt.description {
+        result["description"] = json!(description);
+    }
+    Ok(result)
+}
@@ -1,33 +1,98 @@
 //! MCP protocol types
 
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
 
-/// MCP request
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)]
+use ifm_ruta_core::traits::ValidationError;
+
+/// MCP Request struct like Go
+#[derive(Debug, Deserialize)]
 pub struct MCPRequest {
+    #[allow(dead_code)]
     pub jsonrpc: String,
     pub id: Option<Value>,
     pub method: String,
     pub params: Option<Value>,
 }
 
-/// MCP response
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)]
+/// MCP Response struct like Go
+#[derive(Debug, Serialize)]
 pub struct MCPResponse {
     pub jsonrpc: String,
     pub id: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<MCPError>,
 }
 
-/// MCP error
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)]
+/// MCP Error struct like Go
+#[derive(Debug, Serialize)]
 pub struct MCPError {
     pub code: i32,
     pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<Value>,
 }
+
+impl MCPResponse {
+    /// Create a JSON-RPC error response
+    pub fn error(id: Option<Value>, code: i32, message: impl Into<String>) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(MCPError {
+                code,
+                message: message.into(),
+                data: None,
+            }),
+        }
+    }
+
+    /// Create an `Invalid params` error response for rejected tool arguments
+    ///
+    /// Schema violations carry the JSON pointer and keyword that failed in `data`.
+    pub fn invalid_arguments(id: Option<Value>, error: &ValidationError) -> Self {
+        let data = match error {
+            ValidationError::SchemaViolation {
+                pointer, keyword, ..
+            } => Some(json!({
+                "pointer": pointer,
+                "keyword": keyword
+            })),
+            _ => None,
+        };
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(MCPError {
+                code: error_codes::INVALID_PARAMS,
+                message: error.to_string(),
+                data,
+            }),
+        }
+    }
+}
+
+/// JSON-RPC 2.0 error codes
+pub mod error_codes {
+    /// Invalid JSON was received
+    pub const PARSE_ERROR: i32 = -32700;
+
+    /// The JSON sent is not a valid request object
+    pub const INVALID_REQUEST: i32 = -32600;
+
+    /// The method does not exist
+    pub const METHOD_NOT_FOUND: i32 = -32601;
+
+    /// Invalid method parameters
+    pub const INVALID_PARAMS: i32 = -32602;
+
+    /// Internal JSON-RPC error
+    pub const INTERNAL_ERROR: i32 = -32603;
+
+    /// The requested resource does not exist (MCP)
+    pub const RESOURCE_NOT_FOUND: i32 = -32002;
+}
@@ -0,0 +1,95 @@
+//! Registry of in-flight requests
+
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use ifm_ruta_core::traits::CancellationToken;
+
+/// How many cancellations for not yet registered requests are remembered
+const MAX_EARLY_CANCELS: usize = 64;
+
+/// How long a cancellation waits for its request to be registered
+const EARLY_CANCEL_TTL: Duration = Duration::from_secs(30);
+
+/// In-flight requests keyed by JSON-RPC id
+#[derive(Default)]
+pub struct RequestRegistry {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    requests: HashMap<String, CancellationToken>,
+    /// Cancellations that arrived before their request was registered, since
+    /// requests are handled concurrently and a cancel may be handled first
+    early_cancels: VecDeque<(String, Instant)>,
+}
+
+impl RequestRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Track a request and return its cancellation token, already cancelled
+    /// if the cancellation arrived first
+    pub fn register(&self, id: &Value) -> CancellationToken {
+        let key = Self::key(id);
+        let token = CancellationToken::new();
+        let mut inner = self.inner.lock().unwrap();
+        inner.expire_early_cancels();
+        if let Some(index) = inner
+            .early_cancels
+            .iter()
+            .position(|(early, _)| *early == key)
+        {
+            inner.early_cancels.remove(index);
+            token.cancel();
+        }
+        inner.requests.insert(key, token.clone());
+        token
+    }
+
+    /// Stop tracking a finished request
+    pub fn complete(&self, id: &Value) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.requests.remove(&Self::key(id));
+    }
+
+    /// Cancel a request, returning whether it was still in flight
+    ///
+    /// Unknown ids are remembered for a short while in case their request has
+    /// not been registered yet.
+    pub fn cancel(&self, id: &Value) -> bool {
+        let key = Self::key(id);
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(token) = inner.requests.get(&key) {
+            token.cancel();
+            return true;
+        }
+        inner.expire_early_cancels();
+        if inner.early_cancels.len() == MAX_EARLY_CANCELS {
+            inner.early_cancels.pop_front();
+        }
+        inner.early_cancels.push_back((key, Instant::now()));
+        false
+    }
+
+    /// Ids may be numbers or strings; their JSON text keeps `1` and `"1"` apart
+    fn key(id: &Value) -> String {
+        id.to_string()
+    }
+}
+
+impl Inner {
+    fn expire_early_cancels(&mut self) {
+        while let Some((_, at)) = self.early_cancels.front() {
+            if at.elapsed() < EARLY_CANCEL_TTL {
+                break;
+            }
+            self.early_cancels.pop_front();
+        }
+    }
+}
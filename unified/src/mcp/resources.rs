@@ -0,0 +1,280 @@
+//! Conversation sessions as MCP resources
+//!
+//! Every session stored under `.ifm-ruta/conversations` of a known project
+//! is readable as `ifm-ruta://{project}/sessions/{id}`, where `{project}` is
+//! the name of the project directory followed by a short hash of its
+//! canonical path, e.g. `app-1a2b3c4d`. Sessions are rendered as markdown, or
+//! as the stored JSON with `?format=json`. Projects become known as tool
+//! calls name them and from the client's roots. Clients can subscribe to a
+//! session to hear when its file changes.
+
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use serde_json::{json, Value};
+
+use ifm_ruta_core::models::AppError;
+use ifm_ruta_core::services::{ConversationSession, ConversationStorage};
+
+use super::uri::{percent_decode, percent_encode};
+
+/// Scheme of conversation resource URIs
+const SCHEME: &str = "ifm-ruta://";
+
+const MARKDOWN: &str = "text/markdown";
+const JSON: &str = "application/json";
+
+/// Resource request failure
+#[derive(Debug, thiserror::Error)]
+pub enum ResourceError {
+    #[error("Resource not found: {uri}")]
+    NotFound { uri: String },
+
+    #[error(transparent)]
+    Storage(#[from] AppError),
+}
+
+/// How a session is rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Markdown,
+    Json,
+}
+
+/// Parts of a session URI
+struct SessionUri {
+    project: String,
+    session_id: String,
+    format: Format,
+}
+
+impl SessionUri {
+    fn parse(uri: &str) -> Option<Self> {
+        let rest = uri.strip_prefix(SCHEME)?;
+        let (path, query) = match rest.split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (rest, None),
+        };
+        let format = match query {
+            None | Some("format=markdown") => Format::Markdown,
+            Some("format=json") => Format::Json,
+            Some(_) => return None,
+        };
+
+        let mut segments = path.split('/');
+        let (Some(project), Some("sessions"), Some(session_id), None) = (
+            segments.next(),
+            segments.next(),
+            segments.next(),
+            segments.next(),
+        ) else {
+            return None;
+        };
+        let project = percent_decode(project)?;
+        let session_id = percent_decode(session_id)?;
+
+        // Session ids name files in the storage directory
+        let is_file_name = !session_id.is_empty()
+            && !session_id.starts_with('.')
+            && !session_id.contains(['/', '\\']);
+        (!project.is_empty() && is_file_name).then_some(Self {
+            project,
+            session_id,
+            format,
+        })
+    }
+
+    fn to_uri(&self) -> String {
+        let query = match self.format {
+            Format::Markdown => "",
+            Format::Json => "?format=json",
+        };
+        format!(
+            "{}{}/sessions/{}{}",
+            SCHEME,
+            percent_encode(&self.project),
+            percent_encode(&self.session_id),
+            query
+        )
+    }
+}
+
+/// URI of the markdown rendering of a session
+pub fn session_uri(project_directory: &Path, session_id: &str) -> String {
+    SessionUri {
+        project: project_name(project_directory),
+        session_id: session_id.to_string(),
+        format: Format::Markdown,
+    }
+    .to_uri()
+}
+
+/// Name a project goes by in resource URIs
+///
+/// The hash keeps projects with the same directory name, such as `~/a/app`
+/// and `~/b/app`, apart.
+fn project_name(project_directory: &Path) -> String {
+    let canonical = project_directory
+        .canonicalize()
+        .unwrap_or_else(|_| project_directory.to_path_buf());
+    let name = canonical
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "root".to_string());
+    format!("{}-{:08x}", name, path_hash(&canonical))
+}
+
+/// FNV-1a hash of a path folded to 32 bits, the same across runs and builds
+fn path_hash(path: &Path) -> u32 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in path.to_string_lossy().bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    (hash ^ (hash >> 32)) as u32
+}
+
+/// Conversation sessions of the projects this server has seen
+#[derive(Default)]
+pub struct ConversationResources {
+    projects: RwLock<Vec<PathBuf>>,
+}
+
+impl ConversationResources {
+    /// Remember a project directory so its sessions are listed
+    pub fn add_project(&self, project_directory: PathBuf) {
+        let mut projects = self.projects.write().unwrap();
+        if !projects.contains(&project_directory) {
+            projects.push(project_directory);
+        }
+    }
+
+    /// Known projects followed by `roots`, each directory listed once
+    fn projects(&self, roots: &[PathBuf]) -> Vec<(String, PathBuf)> {
+        let mut projects: Vec<(String, PathBuf)> = Vec::new();
+        for directory in self.projects.read().unwrap().iter().chain(roots) {
+            let name = project_name(directory);
+            if !projects.iter().any(|(known, _)| *known == name) {
+                projects.push((name, directory.clone()));
+            }
+        }
+        projects
+    }
+
+    /// Directories of the known projects followed by `roots`
+    pub fn project_directories(&self, roots: &[PathBuf]) -> Vec<PathBuf> {
+        self.projects(roots)
+            .into_iter()
+            .map(|(_, directory)| directory)
+            .collect()
+    }
+
+    /// Result of `resources/list`
+    pub fn list(&self, roots: &[PathBuf]) -> Result<Value, AppError> {
+        let mut resources = Vec::new();
+        for (project, directory) in self.projects(roots) {
+            let storage = ConversationStorage::new(&directory);
+            if !storage.exists() {
+                continue;
+            }
+            for session in storage.get_project_sessions()? {
+                let uri = SessionUri {
+                    project: project.clone(),
+                    session_id: session.session_id.clone(),
+                    format: Format::Markdown,
+                };
+                resources.push(json!({
+                    "uri": uri.to_uri(),
+                    "name": format!("{}/{}", project, session.session_id),
+                    "description": format!(
+                        "Conversation in {} with {} messages, last updated {}; add ?format=json for the stored JSON",
+                        directory.display(),
+                        session.messages.len(),
+                        session.last_updated
+                    ),
+                    "mimeType": MARKDOWN
+                }));
+            }
+        }
+        Ok(json!({ "resources": resources }))
+    }
+
+    /// Result of `resources/templates/list`
+    pub fn templates() -> Value {
+        json!({
+            "resourceTemplates": [
+                {
+                    "uriTemplate": format!("{}{{project}}/sessions/{{id}}", SCHEME),
+                    "name": "Conversation session",
+                    "description": "Feedback conversation of a project, as a markdown transcript",
+                    "mimeType": MARKDOWN
+                },
+                {
+                    "uriTemplate": format!("{}{{project}}/sessions/{{id}}?format=json", SCHEME),
+                    "name": "Conversation session (JSON)",
+                    "description": "Feedback conversation of a project, as stored",
+                    "mimeType": JSON
+                }
+            ]
+        })
+    }
+
+    /// Storage and parsed URI of a session resource of a known project
+    fn locate(
+        &self,
+        uri: &str,
+        roots: &[PathBuf],
+    ) -> Result<(SessionUri, ConversationStorage), ResourceError> {
+        let not_found = || ResourceError::NotFound {
+            uri: uri.to_string(),
+        };
+        let parsed = SessionUri::parse(uri).ok_or_else(not_found)?;
+        let directory = self
+            .projects(roots)
+            .into_iter()
+            .find(|(name, _)| *name == parsed.project)
+            .map(|(_, directory)| directory)
+            .ok_or_else(not_found)?;
+
+        let storage = ConversationStorage::new(&directory);
+        if !storage.exists() {
+            return Err(not_found());
+        }
+        Ok((parsed, storage))
+    }
+
+    /// File backing a session resource, which need not exist yet
+    pub fn session_file(&self, uri: &str, roots: &[PathBuf]) -> Result<PathBuf, ResourceError> {
+        let (parsed, storage) = self.locate(uri, roots)?;
+        Ok(storage.session_file(&parsed.session_id))
+    }
+
+    /// Result of `resources/read`
+    pub fn read(&self, uri: &str, roots: &[PathBuf]) -> Result<Value, ResourceError> {
+        let (parsed, storage) = self.locate(uri, roots)?;
+        let session =
+            storage
+                .load_session(&parsed.session_id)?
+                .ok_or_else(|| ResourceError::NotFound {
+                    uri: uri.to_string(),
+                })?;
+
+        let (mime_type, text) = match parsed.format {
+            Format::Markdown => (MARKDOWN, session.to_markdown()),
+            Format::Json => (JSON, Self::to_json(&session)?),
+        };
+        Ok(json!({
+            "contents": [{
+                "uri": uri,
+                "mimeType": mime_type,
+                "text": text
+            }]
+        }))
+    }
+
+    fn to_json(session: &ConversationSession) -> Result<String, AppError> {
+        serde_json::to_string_pretty(session).map_err(|e| AppError::SerializationError {
+            message: format!("Failed to serialize session: {}", e),
+        })
+    }
+}
@@ -0,0 +1,77 @@
+//! Filesystem roots exposed by the client
+//!
+//! Clients that declare the `roots` capability answer `roots/list` with the
+//! directories the user opened. They are fetched when first needed and again
+//! after `notifications/roots/list_changed`.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
+use serde_json::Value;
+
+use super::uri::percent_decode;
+
+/// Roots of the connected client
+#[derive(Default)]
+pub struct ClientRoots {
+    supported: AtomicBool,
+    /// `None` until fetched, and again once the client reports a change
+    roots: RwLock<Option<Vec<PathBuf>>>,
+}
+
+impl ClientRoots {
+    /// Remember whether the client declared the `roots` capability
+    pub fn set_supported(&self, supported: bool) {
+        self.supported.store(supported, Ordering::Relaxed);
+    }
+
+    /// Whether the roots should be fetched before they are used
+    pub fn needs_refresh(&self) -> bool {
+        self.supported.load(Ordering::Relaxed) && self.roots.read().unwrap().is_none()
+    }
+
+    /// Roots last reported by the client; empty if it reported none
+    pub fn get(&self) -> Vec<PathBuf> {
+        self.roots.read().unwrap().clone().unwrap_or_default()
+    }
+
+    /// Store the result of `roots/list`; roots that are not `file://` URIs are skipped
+    pub fn update(&self, result: &Value) {
+        let roots = result
+            .get("roots")
+            .and_then(Value::as_array)
+            .map(|roots| {
+                roots
+                    .iter()
+                    .filter_map(|root| root.get("uri").and_then(Value::as_str))
+                    .filter_map(file_uri_to_path)
+                    .collect()
+            })
+            .unwrap_or_default();
+        *self.roots.write().unwrap() = Some(roots);
+    }
+
+    /// Treat the client as having no roots until it reports a change
+    pub fn mark_unavailable(&self) {
+        *self.roots.write().unwrap() = Some(Vec::new());
+    }
+
+    /// Forget the roots so they are fetched again
+    pub fn invalidate(&self) {
+        *self.roots.write().unwrap() = None;
+    }
+}
+
+/// Path of a `file://` URI, decoding percent escapes
+fn file_uri_to_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    // Skip the host, which is empty or `localhost` for local files
+    let path = percent_decode(&rest[rest.find('/')?..])?;
+
+    // `file:///C:/Users` names `C:/Users` on Windows
+    if cfg!(windows) && path.as_bytes().get(2) == Some(&b':') {
+        return Some(PathBuf::from(&path[1..]));
+    }
+    Some(PathBuf::from(path))
+}
@@ -3,48 +3,41 @@
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use ifm_ruta_core::{
     models::AppError,
-    traits::{EventBus, ProcessManager, SettingsManager, Tool},
+    services::validate_against_schema,
+    traits::{
+        EventBus, ProcessManager, ProgressReporter, SettingsManager, Tool, ToolContext, ToolError,
+        EXTRA_CONTENT_KEY,
+    },
 };
 
-/// MCP Request struct like Go
-#[derive(Debug, serde::Deserialize)]
-pub struct MCPRequest {
-    #[allow(dead_code)]
-    pub jsonrpc: String,
-    pub id: Option<Value>,
-    pub method: String,
-    pub params: Option<Value>,
-}
-
-/// MCP Response struct like Go
-#[derive(Debug, serde::Serialize)]
-pub struct MCPResponse {
-    pub jsonrpc: String,
-    pub id: Option<Value>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub result: Option<Value>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub error: Option<MCPError>,
-}
+use super::logging::{ClientLogger, LogLevel};
+use super::peer::{Peer, ProgressNotifier};
+use super::prompts::{get_prompt, load_prompts};
+use super::protocol::{error_codes, MCPRequest, MCPResponse};
+use super::registry::RequestRegistry;
+use super::resources::{ConversationResources, ResourceError};
+use super::roots::ClientRoots;
+use super::subscriptions::ResourceSubscriptions;
 
-/// MCP Error struct like Go
-#[derive(Debug, serde::Serialize)]
-pub struct MCPError {
-    pub code: i32,
-    pub message: String,
-}
+/// How long a cancelled tool call may take to wind down before it is aborted
+const CANCEL_GRACE_PERIOD: Duration = Duration::from_secs(5);
 
 /// MCP server
 pub struct MCPServer {
-    tools: HashMap<String, Box<dyn Tool>>,
-    #[allow(dead_code)]
+    tools: HashMap<String, Arc<dyn Tool>>,
+    requests: RequestRegistry,
+    client_roots: ClientRoots,
+    resources: ConversationResources,
+    subscriptions: ResourceSubscriptions,
+    client_logger: ClientLogger,
+    /// Identifies this connection in tool contexts
+    session_id: String,
     settings_manager: Arc<dyn SettingsManager>,
-    #[allow(dead_code)]
     process_manager: Arc<dyn ProcessManager>,
-    #[allow(dead_code)]
     event_bus: Arc<dyn EventBus>,
 }
 
@@ -57,56 +50,125 @@ impl MCPServer {
     ) -> Self {
         Self {
             tools: HashMap::new(),
+            requests: RequestRegistry::new(),
+            client_roots: ClientRoots::default(),
+            resources: ConversationResources::default(),
+            subscriptions: ResourceSubscriptions::default(),
+            client_logger: ClientLogger::default(),
+            session_id: uuid::Uuid::new_v4().to_string(),
             settings_manager,
             process_manager,
             event_bus,
         }
     }
 
+    /// Send log messages through `client_logger`, whose layer is installed in `tracing`
+    pub fn with_client_logger(mut self, client_logger: ClientLogger) -> Self {
+        self.client_logger = client_logger;
+        self
+    }
+
     /// Register a tool
     pub fn register_tool(&mut self, tool: Box<dyn Tool>) {
         let name = tool.name().to_string();
-        self.tools.insert(name, tool);
+        self.tools.insert(name, Arc::from(tool));
+    }
+
+    /// Start talking to a newly connected client
+    pub fn connect(&self, peer: &Peer) {
+        self.client_logger.connect(peer);
+    }
+
+    /// Release what the connection holds once its input is closed
+    pub fn disconnect(&self) {
+        self.subscriptions.clear();
+        self.client_logger.disconnect();
     }
 
     /// Handle a request like Go
-    pub fn handle_request(&self, request: MCPRequest) -> Result<Option<MCPResponse>, AppError> {
+    ///
+    /// Failures are turned into JSON-RPC error responses; this never fails,
+    /// so one bad request cannot bring the server down.
+    pub async fn handle_request(&self, request: MCPRequest, peer: &Peer) -> Option<MCPResponse> {
         // Check if this is a notification (no id field)
         if request.id.is_none() {
             // Handle notifications silently (no response needed per JSON-RPC 2.0 spec)
             match request.method.as_str() {
                 "notifications/initialized" => {
-                    // Cursor sends this after initialize - just ignore
-                    return Ok(None);
+                    self.client_logger.initialized();
+                    return None;
+                }
+                "notifications/cancelled" => {
+                    self.handle_cancelled(request);
+                    return None;
+                }
+                "notifications/roots/list_changed" => {
+                    self.client_roots.invalidate();
+                    return None;
                 }
                 _ => {
                     // Unknown notification - ignore silently
-                    return Ok(None);
+                    return None;
                 }
             }
         }
 
         // This is a request (has id) - send response
+        let id = request.id.clone();
         let response = match request.method.as_str() {
             "initialize" => self.handle_initialize(request),
+            "ping" => self.handle_ping(request),
             "tools/list" => self.handle_tools_list(request),
-            "tools/call" => self.handle_tool_call(request),
-            _ => Ok(MCPResponse {
-                jsonrpc: "2.0".to_string(),
-                id: request.id,
-                result: None,
-                error: Some(MCPError {
-                    code: -32601,
-                    message: "Method not found".to_string(),
-                }),
-            }),
+            "tools/call" => return self.handle_tool_call(request, peer).await,
+            "resources/list" => self.handle_resources_list(request, peer).await,
+            "resources/templates/list" => self.handle_resource_templates_list(request),
+            "resources/read" => self.handle_resources_read(request, peer).await,
+            "logging/setLevel" => self.handle_set_log_level(request),
+            "prompts/list" => self.handle_prompts_list(request, peer).await,
+            "prompts/get" => self.handle_prompts_get(request, peer).await,
+            "resources/subscribe" => self.handle_resources_subscribe(request, peer).await,
+            "resources/unsubscribe" => self.handle_resources_unsubscribe(request),
+            _ => Ok(MCPResponse::error(
+                id.clone(),
+                error_codes::METHOD_NOT_FOUND,
+                "Method not found",
+            )),
         };
 
-        response.map(Some)
+        Some(response.unwrap_or_else(|e| {
+            tracing::error!("Request failed: {}", e);
+            MCPResponse::error(id, error_codes::INTERNAL_ERROR, e.to_string())
+        }))
+    }
+
+    /// Handle notifications/cancelled by cancelling the matching in-flight request
+    fn handle_cancelled(&self, request: MCPRequest) {
+        let params = request.params.unwrap_or(json!({}));
+        let Some(request_id) = params.get("requestId") else {
+            return;
+        };
+
+        let reason = params
+            .get("reason")
+            .and_then(|v| v.as_str())
+            .unwrap_or("no reason given");
+        if self.requests.cancel(request_id) {
+            tracing::info!("Cancelling request {}: {}", request_id, reason);
+        } else {
+            // Already finished, or not registered yet, in which case the
+            // registry hands the request a cancelled token
+            tracing::debug!("Cancellation for unknown request {}", request_id);
+        }
     }
 
     /// Handle initialize request like Go
     fn handle_initialize(&self, request: MCPRequest) -> Result<MCPResponse, AppError> {
+        let roots = request
+            .params
+            .as_ref()
+            .and_then(|params| params.pointer("/capabilities/roots"));
+        self.client_roots.set_supported(roots.is_some());
+
         Ok(MCPResponse {
             jsonrpc: "2.0".to_string(),
             id: request.id,
@@ -115,7 +177,12 @@ impl MCPServer {
                 "capabilities": {
                     "tools": {
                         "listChanged": true
-                    }
+                    },
+                    "resources": {
+                        "subscribe": true
+                    },
+                    "prompts": {},
+                    "logging": {}
                 },
                 "serverInfo": {
                     "name": "interactive-feedback-mcp",
@@ -126,6 +193,40 @@ impl MCPServer {
         })
     }
 
+    /// Handle ping request
+    fn handle_ping(&self, request: MCPRequest) -> Result<MCPResponse, AppError> {
+        Ok(MCPResponse {
+            jsonrpc: "2.0".to_string(),
+            id: request.id,
+            result: Some(json!({})),
+            error: None,
+        })
+    }
+
+    /// Handle logging/setLevel
+    fn handle_set_log_level(&self, request: MCPRequest) -> Result<MCPResponse, AppError> {
+        let level = request
+            .params
+            .as_ref()
+            .and_then(|params| params.get("level"))
+            .and_then(|level| level.as_str());
+        let Some(level) = level.and_then(LogLevel::parse) else {
+            return Ok(MCPResponse::error(
+                request.id,
+                error_codes::INVALID_PARAMS,
+                "Invalid log level",
+            ));
+        };
+        self.client_logger.set_level(level);
+
+        Ok(MCPResponse {
+            jsonrpc: "2.0".to_string(),
+            id: request.id,
+            result: Some(json!({})),
+            error: None,
+        })
+    }
+
     /// Handle tools/list request like Go
     fn handle_tools_list(&self, request: MCPRequest) -> Result<MCPResponse, AppError> {
         let tools: Vec<Value> = self
@@ -150,34 +251,419 @@ impl MCPServer {
         })
     }
 
-    /// Handle tools/call request like Go
-    fn handle_tool_call(&self, request: MCPRequest) -> Result<MCPResponse, AppError> {
-        let params = request.params.unwrap_or(json!({}));
-        let tool_name = params
-            .get("name")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| AppError::InternalError(anyhow::anyhow!("Missing tool name")))?;
+    /// Handle resources/list with the conversation sessions of known projects
+    async fn handle_resources_list(
+        &self,
+        request: MCPRequest,
+        peer: &Peer,
+    ) -> Result<MCPResponse, AppError> {
+        self.refresh_client_roots(peer).await;
+        let result = self.resources.list(&self.client_roots.get())?;
+
+        Ok(MCPResponse {
+            jsonrpc: "2.0".to_string(),
+            id: request.id,
+            result: Some(result),
+            error: None,
+        })
+    }
 
-        let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+    /// Handle resources/templates/list
+    fn handle_resource_templates_list(&self, request: MCPRequest) -> Result<MCPResponse, AppError> {
+        Ok(MCPResponse {
+            jsonrpc: "2.0".to_string(),
+            id: request.id,
+            result: Some(ConversationResources::templates()),
+            error: None,
+        })
+    }
 
-        let tool = self.tools.get(tool_name).ok_or_else(|| {
-            AppError::InternalError(anyhow::anyhow!("Tool not found: {}", tool_name))
-        })?;
+    /// Handle resources/read
+    async fn handle_resources_read(
+        &self,
+        request: MCPRequest,
+        peer: &Peer,
+    ) -> Result<MCPResponse, AppError> {
+        let Some(uri) = Self::resource_uri(&request) else {
+            return Ok(Self::missing_resource_uri(request.id));
+        };
 
-        // Execute the tool
-        let tool_result = tool.execute(arguments)?;
-        let result_json = serde_json::to_string(&tool_result)?;
+        self.refresh_client_roots(peer).await;
+        let result = match self.resources.read(uri, &self.client_roots.get()) {
+            Ok(result) => result,
+            Err(ResourceError::NotFound { uri }) => {
+                return Ok(Self::resource_not_found(request.id, uri))
+            }
+            Err(ResourceError::Storage(e)) => return Err(e),
+        };
 
         Ok(MCPResponse {
             jsonrpc: "2.0".to_string(),
             id: request.id,
-            result: Some(json!({
-                "content": [{
-                    "type": "text",
-                    "text": result_json
-                }]
-            })),
+            result: Some(result),
             error: None,
         })
     }
+
+    /// Handle resources/subscribe by watching the session's file
+    async fn handle_resources_subscribe(
+        &self,
+        request: MCPRequest,
+        peer: &Peer,
+    ) -> Result<MCPResponse, AppError> {
+        let Some(uri) = Self::resource_uri(&request) else {
+            return Ok(Self::missing_resource_uri(request.id));
+        };
+
+        self.refresh_client_roots(peer).await;
+        let file = match self.resources.session_file(uri, &self.client_roots.get()) {
+            Ok(file) => file,
+            Err(ResourceError::NotFound { uri }) => {
+                return Ok(Self::resource_not_found(request.id, uri))
+            }
+            Err(ResourceError::Storage(e)) => return Err(e),
+        };
+        self.subscriptions.subscribe(uri, file, peer)?;
+
+        Ok(MCPResponse {
+            jsonrpc: "2.0".to_string(),
+            id: request.id,
+            result: Some(json!({})),
+            error: None,
+        })
+    }
+
+    /// Handle resources/unsubscribe
+    fn handle_resources_unsubscribe(&self, request: MCPRequest) -> Result<MCPResponse, AppError> {
+        let Some(uri) = Self::resource_uri(&request) else {
+            return Ok(Self::missing_resource_uri(request.id));
+        };
+        self.subscriptions.unsubscribe(uri);
+
+        Ok(MCPResponse {
+            jsonrpc: "2.0".to_string(),
+            id: request.id,
+            result: Some(json!({})),
+            error: None,
+        })
+    }
+
+    /// The `uri` parameter of a resources request
+    fn resource_uri(request: &MCPRequest) -> Option<&str> {
+        request
+            .params
+            .as_ref()
+            .and_then(|params| params.get("uri"))
+            .and_then(|uri| uri.as_str())
+    }
+
+    fn missing_resource_uri(id: Option<Value>) -> MCPResponse {
+        MCPResponse::error(id, error_codes::INVALID_PARAMS, "Missing resource uri")
+    }
+
+    fn resource_not_found(id: Option<Value>, uri: String) -> MCPResponse {
+        let mut response = MCPResponse::error(
+            id,
+            error_codes::RESOURCE_NOT_FOUND,
+            format!("Resource not found: {}", uri),
+        );
+        if let Some(error) = response.error.as_mut() {
+            error.data = Some(json!({ "uri": uri }));
+        }
+        response
+    }
+
+    /// Handle prompts/list with the built-in, user and project prompt templates
+    async fn handle_prompts_list(
+        &self,
+        request: MCPRequest,
+        peer: &Peer,
+    ) -> Result<MCPResponse, AppError> {
+        self.refresh_client_roots(peer).await;
+        let roots = self.client_roots.get();
+        let prompts: Vec<Value> = load_prompts(&self.resources.project_directories(&roots))
+            .iter()
+            .map(|prompt| prompt.to_listing())
+            .collect();
+
+        Ok(MCPResponse {
+            jsonrpc: "2.0".to_string(),
+            id: request.id,
+            result: Some(json!({ "prompts": prompts })),
+            error: None,
+        })
+    }
+
+    /// Handle prompts/get by filling a template in with the given arguments
+    async fn handle_prompts_get(
+        &self,
+        request: MCPRequest,
+        peer: &Peer,
+    ) -> Result<MCPResponse, AppError> {
+        let params = request.params.unwrap_or(json!({}));
+        let Some(name) = params.get("name").and_then(|name| name.as_str()) else {
+            return Ok(MCPResponse::error(
+                request.id,
+                error_codes::INVALID_PARAMS,
+                "Missing prompt name",
+            ));
+        };
+        let arguments = params
+            .get("arguments")
+            .and_then(|arguments| arguments.as_object())
+            .cloned()
+            .unwrap_or_default();
+
+        self.refresh_client_roots(peer).await;
+        let roots = self.client_roots.get();
+        let prompts = load_prompts(&self.resources.project_directories(&roots));
+        let result = match get_prompt(&prompts, name, &arguments) {
+            Ok(result) => result,
+            // Unknown prompts and missing arguments are invalid params per MCP
+            Err(e) => {
+                return Ok(MCPResponse::error(
+                    request.id,
+                    error_codes::INVALID_PARAMS,
+                    e.to_string(),
+                ))
+            }
+        };
+
+        Ok(MCPResponse {
+            jsonrpc: "2.0".to_string(),
+            id: request.id,
+            result: Some(result),
+            error: None,
+        })
+    }
+
+    /// Handle tools/call request like Go
+    ///
+    /// Malformed calls get JSON-RPC errors; failures inside the tool are
+    /// reported as `isError` results so the model can see and react to them.
+    /// Returns `None` when the client cancelled the call, since cancelled
+    /// requests must not receive a response.
+    async fn handle_tool_call(&self, request: MCPRequest, peer: &Peer) -> Option<MCPResponse> {
+        let id = request.id.clone();
+        let params = request.params.unwrap_or(json!({}));
+
+        let Some(tool_name) = params.get("name").and_then(|v| v.as_str()) else {
+            return Some(MCPResponse::error(
+                id,
+                error_codes::INVALID_PARAMS,
+                "Missing tool name",
+            ));
+        };
+
+        let mut arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+        if !arguments.is_object() {
+            return Some(MCPResponse::error(
+                id,
+                error_codes::INVALID_PARAMS,
+                "Tool arguments must be an object",
+            ));
+        }
+
+        let Some(tool) = self.tools.get(tool_name).cloned() else {
+            return Some(MCPResponse::error(
+                id,
+                error_codes::INVALID_PARAMS,
+                format!("Unknown tool: {}", tool_name),
+            ));
+        };
+
+        // The schema covers types and shapes; tools only check what it cannot express
+        if let Err(e) = validate_against_schema(&tool.input_schema(), &arguments) {
+            return Some(MCPResponse::invalid_arguments(id, &e));
+        }
+        let request_id = id.clone().unwrap_or(Value::Null);
+
+        // A cancel handled before this point is remembered by the registry and
+        // comes back as an already cancelled token
+        let cancellation = self.requests.register(&request_id);
+        let context = ToolContext {
+            cancellation: cancellation.clone(),
+            ..self.tool_context(&request_id, &params, peer).await
+        };
+        if cancellation.is_cancelled() {
+            self.requests.complete(&request_id);
+            return None;
+        }
+        if let Err(e) = tool.validate_input(&mut arguments, &context) {
+            self.requests.complete(&request_id);
+            return Some(MCPResponse::invalid_arguments(id, &e));
+        }
+        self.remember_project(&arguments);
+
+        // Run the tool on its own task so a panic becomes an error response
+        let mut task = tokio::spawn(async move { tool.execute(arguments, &context).await });
+        let outcome = tokio::select! {
+            outcome = &mut task => outcome,
+            _ = cancellation.cancelled() => {
+                // The tool sees the token too; give it time to clean up before aborting
+                if tokio::time::timeout(CANCEL_GRACE_PERIOD, &mut task).await.is_err() {
+                    tracing::warn!("Tool {} ignored cancellation, aborting it", tool_name);
+                    task.abort();
+                }
+                self.requests.complete(&request_id);
+                return None;
+            }
+        };
+        self.requests.complete(&request_id);
+
+        let result = match outcome {
+            Ok(Ok(tool_result)) => Self::tool_success(tool_result),
+            Ok(Err(ToolError::Cancelled)) => return None,
+            Ok(Err(e)) => {
+                tracing::warn!("Tool {} failed: {}", tool_name, e);
+                Self::tool_result(e.to_string(), true)
+            }
+            Err(e) => {
+                tracing::error!("Tool {} panicked: {}", tool_name, e);
+                return Some(MCPResponse::error(
+                    id,
+                    error_codes::INTERNAL_ERROR,
+                    format!("Tool task failed: {}", e),
+                ));
+            }
+        };
+
+        Some(MCPResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: Some(result),
+            error: None,
+        })
+    }
+
+    /// Make the conversations of a tool call's `projectDirectory` readable as resources
+    fn remember_project(&self, arguments: &Value) {
+        let project_directory = arguments
+            .get("projectDirectory")
+            .and_then(|v| v.as_str())
+            .and_then(|path| std::path::Path::new(path).canonicalize().ok());
+        if let Some(project_directory) = project_directory {
+            self.resources.add_project(project_directory);
+        }
+    }
+
+    /// Context for a tool call, reporting progress if the client sent a `progressToken`
+    async fn tool_context(&self, request_id: &Value, params: &Value, peer: &Peer) -> ToolContext {
+        self.refresh_client_roots(peer).await;
+
+        let progress = match params.pointer("/_meta/progressToken") {
+            Some(token) => {
+                ProgressReporter::new(Arc::new(ProgressNotifier::new(peer.clone(), token.clone())))
+            }
+            None => ProgressReporter::default(),
+        };
+
+        ToolContext {
+            request_id: request_id.clone(),
+            session_id: self.session_id.clone(),
+            roots: self.client_roots.get(),
+            progress,
+            ..ToolContext::new(
+                Arc::clone(&self.settings_manager),
+                Arc::clone(&self.process_manager),
+                Arc::clone(&self.event_bus),
+            )
+        }
+    }
+
+    /// Fetch the client's roots if they are not known yet
+    ///
+    /// A client that fails to answer is treated as having no roots, leaving
+    /// only the configured ones in effect, until it sends
+    /// `notifications/roots/list_changed`.
+    async fn refresh_client_roots(&self, peer: &Peer) {
+        if !self.client_roots.needs_refresh() {
+            return;
+        }
+        match peer.request("roots/list", json!({})).await {
+            Ok(result) => self.client_roots.update(&result),
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to list client roots");
+                self.client_roots.mark_unavailable();
+            }
+        }
+    }
+
+    /// Build a successful `CallToolResult`: the JSON result as text, followed by
+    /// any extra content items the tool returned
+    fn tool_success(mut tool_result: Value) -> Value {
+        let extra_content = tool_result
+            .as_object_mut()
+            .and_then(|result| result.remove(EXTRA_CONTENT_KEY));
+
+        let result_json = match serde_json::to_string(&tool_result) {
+            Ok(result_json) => result_json,
+            Err(e) => {
+                return Self::tool_result(format!("Failed to encode tool result: {}", e), true)
+            }
+        };
+
+        let mut result = Self::tool_result(result_json, false);
+        if let Some(Value::Array(items)) = extra_content {
+            if let Some(Value::Array(content)) = result.get_mut("content") {
+                content.extend(items);
+            }
+        }
+        result
+    }
+
+    /// Build a `CallToolResult` with a single text item
+    fn tool_result(text: String, is_error: bool) -> Value {
+        json!({
+            "content": [{
+                "type": "text",
+                "text": text
+            }],
+            "isError": is_error
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ifm_ruta_core::services::{EventBusImpl, ProcessManagerImpl, SettingsManagerImpl};
+
+    use crate::mcp::transport::ProtocolWriter;
+
+    fn server() -> MCPServer {
+        MCPServer::new(
+            Arc::new(SettingsManagerImpl::new()),
+            Arc::new(ProcessManagerImpl::new()),
+            Arc::new(EventBusImpl::new()),
+        )
+    }
+
+    #[tokio::test]
+    async fn progress_is_sent_only_for_a_progress_token() {
+        let server = server();
+        let (writer, mut frames) = ProtocolWriter::recording();
+        let peer = Peer::new(writer);
+
+        let params = json!({ "name": "interactive_feedback", "arguments": {} });
+        let context = server.tool_context(&json!(1), &params, &peer).await;
+        assert!(!context.progress.is_enabled());
+        context.progress.report(1.0, None, Some("Working"));
+        assert!(frames.try_recv().is_err());
+
+        let params = json!({
+            "name": "interactive_feedback",
+            "arguments": {},
+            "_meta": { "progressToken": "feedback-1" }
+        });
+        let context = server.tool_context(&json!(2), &params, &peer).await;
+        assert!(context.progress.is_enabled());
+        context.progress.report(1.0, Some(2.0), Some("Working"));
+        let frame: Value = serde_json::from_str(&frames.try_recv().unwrap()).unwrap();
+        assert_eq!(frame["method"], "notifications/progress");
+        assert_eq!(
+            frame["params"],
+            json!({ "progressToken": "feedback-1", "progress": 1.0, "total": 2.0, "message": "Working" })
+        );
+    }
 }
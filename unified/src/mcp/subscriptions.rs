@@ -0,0 +1,152 @@
+//! Resource subscriptions
+//!
+//! Subscribed sessions are watched through their storage directory, so a
+//! change made by any process - a message added from an open feedback
+//! window, or a session removed by cleanup - reaches the client as
+//! `notifications/resources/updated`.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use notify::{RecursiveMode, Watcher};
+use serde_json::json;
+
+use ifm_ruta_core::models::AppError;
+
+use super::peer::Peer;
+
+/// How long to wait for related file events, such as the truncate and write
+/// of a single save, before notifying
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Subscribed resource URIs and the files backing them
+#[derive(Default)]
+struct Subscribed {
+    files: HashMap<String, PathBuf>,
+}
+
+impl Subscribed {
+    /// URIs backed by any of `paths`
+    fn uris_for(&self, paths: &HashSet<PathBuf>) -> Vec<String> {
+        self.files
+            .iter()
+            .filter(|(_, file)| paths.contains(*file))
+            .map(|(uri, _)| uri.clone())
+            .collect()
+    }
+
+    fn watches(&self, directory: &Path) -> bool {
+        self.files
+            .values()
+            .any(|file| file.parent() == Some(directory))
+    }
+}
+
+/// Resources the client subscribed to
+#[derive(Default)]
+pub struct ResourceSubscriptions {
+    subscribed: Arc<Mutex<Subscribed>>,
+    /// Created on the first subscription, as it needs the peer to notify
+    watcher: Mutex<Option<notify::RecommendedWatcher>>,
+}
+
+impl ResourceSubscriptions {
+    /// Subscribe to `uri`, backed by `file`
+    pub fn subscribe(&self, uri: &str, file: PathBuf, peer: &Peer) -> Result<(), AppError> {
+        let directory =
+            file.parent()
+                .map(Path::to_path_buf)
+                .ok_or_else(|| AppError::StorageError {
+                    message: format!("No directory to watch for {}", file.display()),
+                })?;
+
+        let mut watcher = self.watcher.lock().unwrap();
+        if watcher.is_none() {
+            *watcher = Some(self.start_watcher(peer.clone())?);
+        }
+
+        let mut subscribed = self.subscribed.lock().unwrap();
+        if !subscribed.watches(&directory) {
+            if let Some(watcher) = watcher.as_mut() {
+                watcher
+                    .watch(&directory, RecursiveMode::NonRecursive)
+                    .map_err(|e| AppError::StorageError {
+                        message: format!("Failed to watch {}: {}", directory.display(), e),
+                    })?;
+            }
+        }
+        subscribed.files.insert(uri.to_string(), file);
+        Ok(())
+    }
+
+    /// Drop the subscription to `uri`; unknown URIs are ignored
+    pub fn unsubscribe(&self, uri: &str) {
+        // Same lock order as `subscribe`
+        let mut watcher = self.watcher.lock().unwrap();
+        let mut subscribed = self.subscribed.lock().unwrap();
+        let Some(file) = subscribed.files.remove(uri) else {
+            return;
+        };
+
+        let Some(directory) = file.parent() else {
+            return;
+        };
+        if subscribed.watches(directory) {
+            return;
+        }
+        if let Some(watcher) = watcher.as_mut() {
+            if let Err(e) = watcher.unwatch(directory) {
+                tracing::debug!("Failed to unwatch {}: {}", directory.display(), e);
+            }
+        }
+    }
+
+    /// Drop every subscription and stop watching
+    ///
+    /// The watcher holds on to the peer, so this must happen before the
+    /// connection can shut down.
+    pub fn clear(&self) {
+        let mut watcher = self.watcher.lock().unwrap();
+        self.subscribed.lock().unwrap().files.clear();
+        *watcher = None;
+    }
+
+    /// Watch for file events, notifying `peer` of the subscribed ones
+    fn start_watcher(&self, peer: Peer) -> Result<notify::RecommendedWatcher, AppError> {
+        let (sender, receiver) = mpsc::channel::<notify::Result<notify::Event>>();
+        let watcher = notify::recommended_watcher(sender).map_err(|e| AppError::StorageError {
+            message: format!("Failed to start file watcher: {}", e),
+        })?;
+
+        let subscribed = Arc::clone(&self.subscribed);
+        std::thread::spawn(move || {
+            // Ends once the watcher is dropped along with the sender
+            while let Ok(event) = receiver.recv() {
+                let mut changed = HashSet::new();
+                let mut collect = |event: notify::Result<notify::Event>| match event {
+                    Ok(event) if !event.kind.is_access() => changed.extend(event.paths),
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("File watcher error: {}", e),
+                };
+                collect(event);
+                while let Ok(event) = receiver.recv_timeout(DEBOUNCE) {
+                    collect(event);
+                }
+
+                let uris = subscribed.lock().unwrap().uris_for(&changed);
+                for uri in uris {
+                    if let Err(e) =
+                        peer.notify("notifications/resources/updated", json!({ "uri": uri }))
+                    {
+                        tracing::warn!("Failed to notify update of {}: {}", uri, e);
+                    }
+                }
+            }
+        });
+
+        Ok(watcher)
+    }
+}
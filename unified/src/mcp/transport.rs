@@ -0,0 +1,199 @@
+//! Async stdio transport for the MCP server
+//!
+//! Every request is dispatched on its own task so a long-running tool call
+//! (e.g. a feedback window waiting for the user) does not block `ping` or
+//! `tools/list`. All responses go through a single writer task that owns
+//! stdout, so frames are never interleaved. Responses from the client to
+//! requests the server sent are handed to the [`Peer`].
+
+use std::sync::Arc;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+
+use ifm_ruta_core::models::AppError;
+
+use super::peer::Peer;
+use super::protocol::error_codes;
+use super::protocol::{MCPRequest, MCPResponse};
+use super::server::MCPServer;
+
+/// Serialized writer for outgoing JSON-RPC frames
+#[derive(Clone)]
+pub struct ProtocolWriter {
+    sender: mpsc::UnboundedSender<String>,
+}
+
+impl ProtocolWriter {
+    /// Spawn the writer task that owns stdout
+    fn spawn() -> (Self, tokio::task::JoinHandle<()>) {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<String>();
+
+        let task = tokio::spawn(async move {
+            let mut stdout = tokio::io::stdout();
+            while let Some(frame) = receiver.recv().await {
+                let written = async {
+                    stdout.write_all(frame.as_bytes()).await?;
+                    stdout.write_all(b"\n").await?;
+                    stdout.flush().await
+                };
+                if let Err(e) = written.await {
+                    tracing::error!("Failed to write to stdout: {}", e);
+                    break;
+                }
+            }
+        });
+
+        (Self { sender }, task)
+    }
+
+    /// Writer that hands frames to the returned receiver instead of stdout
+    #[cfg(test)]
+    pub fn recording() -> (Self, mpsc::UnboundedReceiver<String>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Self { sender }, receiver)
+    }
+
+    /// Queue a message to be written as a single line
+    pub fn send<T: serde::Serialize>(&self, message: &T) -> Result<(), AppError> {
+        let frame = serde_json::to_string(message)?;
+        self.sender
+            .send(frame)
+            .map_err(|_| AppError::InternalError(anyhow::anyhow!("Protocol writer closed")))
+    }
+}
+
+/// Serve JSON-RPC requests from stdin until EOF
+///
+/// Lines that are not valid UTF-8 or JSON get a parse error response; only
+/// the end of input stops the loop.
+pub async fn serve_stdio(server: Arc<MCPServer>) -> Result<(), AppError> {
+    let (writer, writer_task) = ProtocolWriter::spawn();
+    let peer = Peer::new(writer.clone());
+    server.connect(&peer);
+    let mut in_flight = JoinSet::new();
+
+    let mut stdin = BufReader::new(tokio::io::stdin());
+    let mut buffer = Vec::new();
+    loop {
+        buffer.clear();
+        match stdin.read_until(b'\n', &mut buffer).await {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                // The client can no longer reach us, so treat it like EOF
+                tracing::error!("Failed to read from stdin: {}", e);
+                break;
+            }
+        }
+
+        let line = match std::str::from_utf8(&buffer) {
+            Ok(line) => line.trim(),
+            Err(e) => {
+                tracing::warn!("Received invalid UTF-8: {}", e);
+                writer.send(&MCPResponse::error(
+                    None,
+                    error_codes::PARSE_ERROR,
+                    format!("Parse error: {}", e),
+                ))?;
+                continue;
+            }
+        };
+        if line.is_empty() {
+            continue;
+        }
+
+        let request = match parse_message(line) {
+            Ok(Message::Request(request)) => request,
+            Ok(Message::Response { id, outcome }) => {
+                if !peer.handle_response(&id, outcome) {
+                    tracing::debug!("Ignoring response to unknown request {}", id);
+                }
+                continue;
+            }
+            Err(response) => {
+                writer.send(&response)?;
+                continue;
+            }
+        };
+
+        let server = Arc::clone(&server);
+        let writer = writer.clone();
+        let peer = peer.clone();
+        in_flight.spawn(async move {
+            // Notifications don't get responses (per JSON-RPC 2.0 spec)
+            if let Some(response) = server.handle_request(request, &peer).await {
+                if let Err(e) = writer.send(&response) {
+                    tracing::error!("Failed to queue response: {}", e);
+                }
+            }
+        });
+
+        // Reap finished requests so the set does not grow unbounded
+        while in_flight.try_join_next().is_some() {}
+    }
+
+    // Input closed: let pending requests finish before shutting down the writer
+    while in_flight.join_next().await.is_some() {}
+    server.disconnect();
+    drop(peer);
+    drop(writer);
+    let _ = writer_task.await;
+
+    Ok(())
+}
+
+/// Message received from the client
+enum Message {
+    Request(MCPRequest),
+    /// Answer to a request the server sent
+    Response {
+        id: serde_json::Value,
+        outcome: Result<serde_json::Value, serde_json::Value>,
+    },
+}
+
+/// Parse one input line, producing the error response to send if it is not a valid message
+fn parse_message(line: &str) -> Result<Message, Box<MCPResponse>> {
+    let value: serde_json::Value = serde_json::from_str(line).map_err(|e| {
+        tracing::warn!("Received invalid JSON: {}", e);
+        Box::new(MCPResponse::error(
+            None,
+            error_codes::PARSE_ERROR,
+            format!("Parse error: {}", e),
+        ))
+    })?;
+
+    // Responses carry a result or an error instead of a method
+    if value.get("method").is_none() {
+        if let Some(id) = value.get("id").filter(|id| !id.is_null()) {
+            if let Some(result) = value.get("result") {
+                return Ok(Message::Response {
+                    id: id.clone(),
+                    outcome: Ok(result.clone()),
+                });
+            }
+            if let Some(error) = value.get("error") {
+                return Ok(Message::Response {
+                    id: id.clone(),
+                    outcome: Err(error.clone()),
+                });
+            }
+        }
+    }
+
+    // Echo the id back when the message has one, even if the rest is malformed
+    let id = value.get("id").cloned();
+    serde_json::from_value(value)
+        .map(Message::Request)
+        .map_err(|e| {
+            tracing::warn!("Received invalid request: {}", e);
+            Box::new(MCPResponse::error(
+                id,
+                error_codes::INVALID_REQUEST,
+                format!("Invalid request: {}", e),
+            ))
+        })
+}
@@ -0,0 +1,140 @@
+//! Scrollable view of command output with ANSI colors
+
+use std::collections::VecDeque;
+
+use eframe::egui::{self, text::LayoutJob, Color32, FontId, Stroke, TextFormat};
+use ifm_ruta_core::utils::{AnsiColor, AnsiStyle, StyledSpan};
+
+/// Lines kept on screen; older ones scroll away for good
+pub const MAX_LINES: usize = 5000;
+
+/// The 16 standard terminal colors, close to xterm's defaults
+const STANDARD_COLORS: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 49, 49),
+    (13, 188, 121),
+    (229, 229, 16),
+    (36, 114, 200),
+    (188, 63, 188),
+    (17, 168, 205),
+    (229, 229, 229),
+    (102, 102, 102),
+    (241, 76, 76),
+    (35, 209, 139),
+    (245, 245, 67),
+    (59, 142, 234),
+    (214, 112, 214),
+    (41, 184, 219),
+    (255, 255, 255),
+];
+
+/// Color of a palette entry or 24-bit color
+fn color32(color: AnsiColor) -> Color32 {
+    match color {
+        AnsiColor::Indexed(index @ 0..=15) => {
+            let (r, g, b) = STANDARD_COLORS[index as usize];
+            Color32::from_rgb(r, g, b)
+        }
+        // 6x6x6 color cube
+        AnsiColor::Indexed(index @ 16..=231) => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let index = index - 16;
+            Color32::from_rgb(level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        // Grayscale ramp
+        AnsiColor::Indexed(index) => Color32::from_gray(8 + (index - 232) * 10),
+        AnsiColor::Rgb(r, g, b) => Color32::from_rgb(r, g, b),
+    }
+}
+
+/// Output of the commands run in the panel, line by line
+#[derive(Default)]
+pub struct TerminalView {
+    lines: VecDeque<Vec<StyledSpan>>,
+}
+
+impl TerminalView {
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Append a line of command output
+    pub fn push_spans(&mut self, spans: Vec<StyledSpan>) {
+        if self.lines.len() == MAX_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(spans);
+    }
+
+    /// Append a line in the default style
+    pub fn push_plain(&mut self, text: &str) {
+        self.push_spans(vec![StyledSpan {
+            text: text.to_string(),
+            style: AnsiStyle::default(),
+        }]);
+    }
+
+    /// Render the output, following new lines as they arrive
+    pub fn show(&self, ui: &mut egui::Ui) {
+        egui::ScrollArea::vertical()
+            .id_source("command_output")
+            .max_height(200.0)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                let mut job = self.layout_job(ui);
+                job.wrap.max_width = ui.available_width();
+                ui.add(egui::Label::new(job));
+            });
+    }
+
+    fn layout_job(&self, ui: &egui::Ui) -> LayoutJob {
+        let visuals = ui.visuals();
+        let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+        let mut job = LayoutJob::default();
+        for (i, line) in self.lines.iter().enumerate() {
+            if i > 0 {
+                job.append("\n", 0.0, Self::format(&font_id, visuals.text_color()));
+            }
+            for span in line {
+                job.append(&span.text, 0.0, Self::span_format(&font_id, visuals, span));
+            }
+        }
+        job
+    }
+
+    fn format(font_id: &FontId, color: Color32) -> TextFormat {
+        TextFormat {
+            font_id: font_id.clone(),
+            color,
+            ..Default::default()
+        }
+    }
+
+    fn span_format(font_id: &FontId, visuals: &egui::Visuals, span: &StyledSpan) -> TextFormat {
+        let style = &span.style;
+        // The monospace font has no bold face, so bold text is drawn brighter
+        let default_foreground = if style.bold {
+            visuals.strong_text_color()
+        } else {
+            visuals.text_color()
+        };
+        let mut foreground = style.foreground.map_or(default_foreground, color32);
+        let mut background = style.background.map(color32);
+        if style.inverse {
+            let swapped = background.unwrap_or(visuals.extreme_bg_color);
+            background = Some(foreground);
+            foreground = swapped;
+        }
+        if style.dim {
+            foreground = foreground.gamma_multiply(0.6);
+        }
+
+        let mut format = Self::format(font_id, foreground);
+        format.background = background.unwrap_or(Color32::TRANSPARENT);
+        format.italics = style.italic;
+        if style.underline {
+            format.underline = Stroke::new(1.0, foreground);
+        }
+        format
+    }
+}
@@ -1,11 +1,88 @@
 //! Interactive feedback tool implementation
 
 use serde_json::{json, Value};
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use ifm_ruta_core::models::{
+    check_feedback_protocol_version, FeedbackAttachment, FeedbackOption, FeedbackOutcome,
+    FeedbackRequest, FeedbackResult, FeedbackStatus, FeedbackStatusUpdate, FormSchema,
+};
+use ifm_ruta_core::services::{ConversationMessage, PathPolicy};
+use ifm_ruta_core::traits::{
+    ProgressReporter, Tool, ToolContext, ToolError, ValidationError, EXTRA_CONTENT_KEY,
+};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{ChildStdout, Command};
+use tokio::sync::mpsc;
+
+use crate::mcp::resources::session_uri;
+
+/// Session that holds the current conversation
+const CURRENT_SESSION_ID: &str = "current-conversation";
+
+/// Extra time the GUI gets to close itself after its timeout before it is killed
+const GUI_TIMEOUT_GRACE: Duration = Duration::from_secs(5);
+
+/// Longest time between two progress updates while the window is open
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Progress of a feedback round, reported to callers that sent a `progressToken`
+struct WaitProgress<'a> {
+    reporter: &'a ProgressReporter,
+    /// Updates sent so far; progress must increase with every notification
+    updates: u32,
+    /// What the window is doing
+    state: &'static str,
+    started_at: Instant,
+}
+
+impl<'a> WaitProgress<'a> {
+    fn new(reporter: &'a ProgressReporter) -> Self {
+        Self {
+            reporter,
+            updates: 0,
+            state: "Opening feedback window",
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Report a status the window sent
+    fn window_status(&mut self, status: FeedbackStatus) {
+        self.state = match status {
+            FeedbackStatus::Opened => "Feedback window opened",
+            FeedbackStatus::Typing => "User is typing",
+        };
+        self.send(self.state.to_string());
+    }
+
+    /// Repeat the current state so the caller knows the call is alive
+    fn keep_alive(&mut self) {
+        let waited = self.started_at.elapsed().as_secs();
+        self.send(format!("{} ({}s elapsed)", self.state, waited));
+    }
+
+    /// Report how the round ended
+    fn finished(&mut self, outcome: FeedbackOutcome) {
+        let message = match outcome {
+            FeedbackOutcome::Submitted => "Feedback submitted",
+            FeedbackOutcome::Cancelled => "Feedback window closed without a reply",
+            FeedbackOutcome::Timeout => "Feedback timed out",
+        };
+        self.send(message.to_string());
+    }
 
-use ifm_ruta_core::traits::{Tool, ToolError, ValidationError};
+    fn send(&mut self, message: String) {
+        self.updates += 1;
+        self.reporter
+            .report(f64::from(self.updates), None, Some(&message));
+    }
+}
 
 /// Interactive feedback tool
+#[derive(Default)]
 pub struct InteractiveFeedbackTool;
 
 impl InteractiveFeedbackTool {
@@ -14,84 +91,267 @@ impl InteractiveFeedbackTool {
         Self
     }
 
-    // Removed unused methods: get_conversation_history and create_feedback_with_history
-
-    /// Create conversation history in Go format
-    fn create_conversation_history_format(
+    /// Canonical form of `projectDirectory`, checked against the allowed roots
+    fn project_directory(
         &self,
-        previous_user_request: &str,
-        prompt: &str,
-    ) -> Result<Value, ToolError> {
-        use chrono::Utc;
-        use uuid::Uuid;
-
-        let conversation_history = json!([
-            {
-                "id": Uuid::new_v4().to_string(),
-                "timestamp": Utc::now().to_rfc3339(),
-                "role": "user",
-                "content": previous_user_request,
-                "is_current": false
-            },
-            {
-                "id": Uuid::new_v4().to_string(),
-                "timestamp": Utc::now().to_rfc3339(),
-                "role": "assistant",
-                "content": prompt,
-                "is_current": false
-            }
-        ]);
+        input: &Value,
+        context: &ToolContext,
+    ) -> Result<PathBuf, ValidationError> {
+        let project_directory = input
+            .get("projectDirectory")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ValidationError::MissingField {
+                field: "projectDirectory".to_string(),
+            })?;
 
-        Ok(conversation_history)
+        let security = context.settings.get_settings().security;
+        let mut policy = PathPolicy::from_settings(&security);
+        if security.use_client_roots {
+            policy = policy.with_roots(context.roots.iter().cloned());
+        }
+        policy
+            .check_directory(Path::new(project_directory))
+            .map_err(|e| match e {
+                ValidationError::InvalidInput { message } => ValidationError::InvalidInput {
+                    message: format!("projectDirectory: {}", message),
+                },
+                e => e,
+            })
     }
 
     /// Run interactive feedback with egui GUI (Rust native)
-    fn run_interactive_feedback_with_gui(
+    ///
+    /// While the window is open, progress is reported as it changes and at
+    /// least every `KEEP_ALIVE_INTERVAL`, so clients that reset their timeout
+    /// on progress keep waiting for the user.
+    async fn run_interactive_feedback_with_gui(
         &self,
-        project_directory: &str,
-        prompt: &str,
-    ) -> Result<String, ToolError> {
+        request: &FeedbackRequest,
+        context: &ToolContext,
+    ) -> Result<FeedbackResult, ToolError> {
         // Use the current unified executable for GUI mode
         let current_exe = std::env::current_exe().map_err(|e| ToolError::ExecutionError {
             message: format!("Failed to get executable path: {}", e),
         })?;
 
-        // Run unified executable in GUI mode with project directory and prompt as arguments
-        let output = Command::new(current_exe)
-            .arg(project_directory)
-            .arg(prompt)
-            .output()
-            .map_err(|e| ToolError::ExecutionError {
-                message: format!("Failed to run GUI: {}", e),
+        let request_json = serde_json::to_vec(&request).map_err(|e| ToolError::ExecutionError {
+            message: format!("Failed to encode feedback request: {}", e),
+        })?;
+
+        // Run unified executable in GUI mode; the request goes over stdin so
+        // prompts of any size or content survive intact. The window is closed
+        // if this call is dropped, e.g. when the server aborts it.
+        let mut child = Command::new(current_exe)
+            .arg("--feedback-request")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                tracing::error!(error = %e, "Failed to start the feedback window");
+                ToolError::ExecutionError {
+                    message: format!("Failed to run GUI: {}", e),
+                }
             })?;
 
-        if !output.status.success() {
+        if let Some(mut stdin) = child.stdin.take() {
+            // Dropping stdin at the end of this block signals end of request
+            if let Err(e) = stdin.write_all(&request_json).await {
+                let _ = child.kill().await;
+                return Err(ToolError::ExecutionError {
+                    message: format!("Failed to send feedback request to GUI: {}", e),
+                });
+            }
+        }
+
+        // Drain the pipes on separate tasks so the child never blocks on a full pipe
+        let (status_sender, mut statuses) = mpsc::unbounded_channel();
+        let stdout_reader = Self::spawn_stdout_reader(child.stdout.take(), status_sender);
+        let stderr_reader = Self::spawn_pipe_reader(child.stderr.take());
+
+        // The window closes itself on timeout; this is the backstop if it hangs
+        let started_at = Instant::now();
+        let deadline = request
+            .timeout()
+            .map(|timeout| tokio::time::Instant::now() + timeout + GUI_TIMEOUT_GRACE);
+        let backstop = async {
+            match deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::pin!(backstop);
+
+        let mut progress = WaitProgress::new(&context.progress);
+        let mut keep_alive = tokio::time::interval_at(
+            tokio::time::Instant::now() + KEEP_ALIVE_INTERVAL,
+            KEEP_ALIVE_INTERVAL,
+        );
+
+        let status = loop {
+            tokio::select! {
+                status = child.wait() => break status.map_err(|e| ToolError::ExecutionError {
+                    message: format!("Failed to wait for GUI: {}", e),
+                })?,
+                Some(status) = statuses.recv() => progress.window_status(status),
+                _ = keep_alive.tick() => progress.keep_alive(),
+                _ = context.cancellation.cancelled() => {
+                    let _ = child.kill().await;
+                    return Err(ToolError::Cancelled);
+                }
+                _ = &mut backstop => {
+                    tracing::warn!("Feedback window did not close after timeout, killing it");
+                    let _ = child.kill().await;
+                    progress.finished(FeedbackOutcome::Timeout);
+                    return Ok(FeedbackResult::new(
+                        FeedbackOutcome::Timeout,
+                        String::new(),
+                        started_at.elapsed(),
+                    ));
+                }
+            }
+        };
+
+        let stdout = stdout_reader.await.unwrap_or_default();
+        let stderr = stderr_reader.await.unwrap_or_default();
+
+        if !status.success() {
+            tracing::error!(status = %status, stderr = %stderr.trim(), "Feedback window failed");
             return Err(ToolError::ExecutionError {
-                message: format!(
-                    "egui GUI failed: {}",
-                    String::from_utf8_lossy(&output.stderr)
-                ),
+                message: format!("egui GUI failed: {}", stderr),
             });
         }
 
-        let user_feedback = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        Ok(user_feedback)
+        let result =
+            Self::parse_feedback_result(&stdout).map_err(|message| ToolError::ExecutionError {
+                message: format!("{}: {}", message, stderr),
+            })?;
+        progress.finished(result.outcome);
+        Ok(result)
+    }
+
+    /// Read the GUI's stdout on a background task, passing status updates on
+    /// and keeping the other lines
+    fn spawn_stdout_reader(
+        pipe: Option<ChildStdout>,
+        statuses: mpsc::UnboundedSender<FeedbackStatus>,
+    ) -> tokio::task::JoinHandle<String> {
+        tokio::spawn(async move {
+            let mut output = String::new();
+            let Some(pipe) = pipe else {
+                return output;
+            };
+
+            let mut reader = BufReader::new(pipe);
+            let mut line = Vec::new();
+            while reader.read_until(b'\n', &mut line).await.unwrap_or(0) > 0 {
+                let text = String::from_utf8_lossy(&line);
+                match serde_json::from_str::<FeedbackStatusUpdate>(text.trim()) {
+                    Ok(update) if check_feedback_protocol_version(update.version).is_ok() => {
+                        let _ = statuses.send(update.status);
+                    }
+                    _ => output.push_str(&text),
+                }
+                line.clear();
+            }
+            output
+        })
+    }
+
+    /// Find the result envelope in the GUI output
+    ///
+    /// The envelope is the last JSON line; anything a library printed before it is skipped.
+    fn parse_feedback_result(stdout: &str) -> Result<FeedbackResult, String> {
+        let result: FeedbackResult = stdout
+            .lines()
+            .rev()
+            .find_map(|line| serde_json::from_str(line.trim()).ok())
+            .ok_or("egui GUI returned no feedback result")?;
+        check_feedback_protocol_version(result.version)
+            .map_err(|e| format!("egui GUI returned an unusable result: {}", e))?;
+        Ok(result)
+    }
+
+    /// Read a child pipe to completion on a background task
+    fn spawn_pipe_reader<R: AsyncRead + Unpin + Send + 'static>(
+        pipe: Option<R>,
+    ) -> tokio::task::JoinHandle<String> {
+        tokio::spawn(async move {
+            let mut buffer = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buffer).await;
+            }
+            String::from_utf8_lossy(&buffer).into_owned()
+        })
+    }
+
+    /// Check what the schema cannot express about the quick-reply options,
+    /// returning how many there are
+    fn validate_options(&self, options: &Value) -> Result<usize, ValidationError> {
+        let options = options.as_array().map(Vec::as_slice).unwrap_or_default();
+
+        let mut labels = std::collections::HashSet::new();
+        for (i, option) in options.iter().enumerate() {
+            let label = option
+                .get("label")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default();
+            if label.trim().is_empty() || !labels.insert(label) {
+                return Err(ValidationError::InvalidInput {
+                    message: format!("options[{}].label must be non-empty and unique", i),
+                });
+            }
+        }
+
+        let defaults = options
+            .iter()
+            .filter(|option| option.get("default") == Some(&Value::Bool(true)))
+            .count();
+        if defaults > 1 {
+            return Err(ValidationError::InvalidInput {
+                message: "At most one option can be the default".to_string(),
+            });
+        }
+
+        Ok(options.len())
+    }
+
+    /// Parse and check a form definition
+    fn parse_form(form: &Value) -> Result<FormSchema, ValidationError> {
+        let form: FormSchema =
+            serde_json::from_value(form.clone()).map_err(|e| ValidationError::InvalidInput {
+                message: format!("Invalid form: {}", e),
+            })?;
+        form.validate_definition()?;
+        Ok(form)
+    }
+
+    /// Record in storage that the current feedback round was cancelled
+    fn mark_round_cancelled(&self, project_directory: &Path) {
+        use ifm_ruta_core::services::ConversationStorage;
+
+        let storage = ConversationStorage::new(project_directory);
+        if let Err(e) = storage.mark_cancelled(CURRENT_SESSION_ID) {
+            tracing::warn!("Failed to mark feedback round as cancelled: {}", e);
+        }
     }
 
     /// Save real conversation to storage - append to current conversation only
+    ///
+    /// Returns the stored prompt.
     fn save_real_conversation(
         &self,
-        project_directory: &str,
+        project_directory: &Path,
         previous_user_request: &str,
         prompt: &str,
-    ) -> Result<(), ToolError> {
+    ) -> Result<ConversationMessage, ToolError> {
         use ifm_ruta_core::services::ConversationStorage;
-        use std::path::Path;
 
         // Setup project directory with .gitignore and README
         self.setup_project_directory(project_directory)?;
 
-        let storage = ConversationStorage::new(Path::new(project_directory));
+        let storage = ConversationStorage::new(project_directory);
         storage
             .initialize()
             .map_err(|e| ToolError::ExecutionError {
@@ -99,10 +359,17 @@ impl InteractiveFeedbackTool {
             })?;
 
         // Clean up old conversation files, keep only current conversation
-        let _ = storage.cleanup_old_sessions(1); // Keep only 1 session (current)
+        if let Err(e) = storage.cleanup_old_sessions(1) {
+            // Keep only 1 session (current); stale ones are harmless
+            tracing::warn!(
+                project = %project_directory.display(),
+                error = %e,
+                "Failed to clean up old conversations"
+            );
+        }
 
         // Use fixed session ID for current conversation only
-        let session_id = "current-conversation";
+        let session_id = CURRENT_SESSION_ID;
 
         // Add user message if not empty
         if !previous_user_request.is_empty() {
@@ -114,30 +381,73 @@ impl InteractiveFeedbackTool {
         }
 
         // Add assistant message
-        storage
-            .add_message(session_id, "assistant", prompt)
+        let message = storage
+            .add_message_with_attachments(session_id, "assistant", prompt, Vec::new())
             .map_err(|e| ToolError::ExecutionError {
                 message: format!("Failed to add assistant message: {}", e),
             })?;
 
-        println!("Added messages to current conversation: {}", session_id);
-        Ok(())
+        tracing::debug!("Added messages to current conversation: {}", session_id);
+        Ok(message)
+    }
+
+    /// Store the user's reply, with references to the files they attached
+    fn save_feedback_reply(
+        &self,
+        project_directory: &Path,
+        feedback_result: &FeedbackResult,
+    ) -> Result<ConversationMessage, ToolError> {
+        use ifm_ruta_core::services::ConversationStorage;
+
+        let storage = ConversationStorage::new(project_directory);
+        storage
+            .add_message_with_attachments(
+                CURRENT_SESSION_ID,
+                "user",
+                &feedback_result.text,
+                feedback_result.attachments.clone(),
+            )
+            .map_err(|e| ToolError::ExecutionError {
+                message: format!("Failed to add feedback message: {}", e),
+            })
+    }
+
+    /// Turn attached images into MCP `image` content items
+    fn image_content(attachments: &[FeedbackAttachment]) -> Result<Vec<Value>, ToolError> {
+        attachments
+            .iter()
+            .filter(|attachment| {
+                FeedbackAttachment::IMAGE_MIME_TYPES.contains(&attachment.mime_type.as_str())
+            })
+            .map(|attachment| {
+                let bytes =
+                    std::fs::read(&attachment.path).map_err(|e| ToolError::ExecutionError {
+                        message: format!(
+                            "Failed to read attachment {}: {}",
+                            attachment.path.display(),
+                            e
+                        ),
+                    })?;
+                Ok(json!({
+                    "type": "image",
+                    "data": base64::engine::general_purpose::STANDARD.encode(bytes),
+                    "mimeType": attachment.mime_type
+                }))
+            })
+            .collect()
     }
 
     /// Setup project directory with .gitignore and README
-    fn setup_project_directory(&self, project_directory: &str) -> Result<(), ToolError> {
+    fn setup_project_directory(&self, project_directory: &Path) -> Result<(), ToolError> {
         use std::fs;
-        use std::path::Path;
-
-        let project_path = Path::new(project_directory);
 
         // Create .ifm-ruta directory if it doesn't exist
-        let ifm_ruta_dir = project_path.join(".ifm-ruta");
+        let ifm_ruta_dir = project_directory.join(".ifm-ruta");
         if !ifm_ruta_dir.exists() {
             fs::create_dir_all(&ifm_ruta_dir).map_err(|e| ToolError::ExecutionError {
                 message: format!("Failed to create .ifm-ruta directory: {}", e),
             })?;
-            println!("Created .ifm-ruta directory");
+            tracing::info!("Created .ifm-ruta directory");
         }
 
         // Create README.md in .ifm-ruta directory
@@ -150,6 +460,7 @@ This directory contains conversation history between users and the IFM-Ruta MCP
 ## Directory Structure
 
 - `conversations/` - Contains JSON files with conversation sessions
+- `attachments/` - Contains images attached to feedback replies
 - `README.md` - This file explaining the directory purpose
 
 ## Files
@@ -172,7 +483,7 @@ Do not manually modify the files in this directory.
             fs::write(&readme_path, readme_content).map_err(|e| ToolError::ExecutionError {
                 message: format!("Failed to create README.md: {}", e),
             })?;
-            println!("Created README.md in .ifm-ruta directory");
+            tracing::info!("Created README.md in .ifm-ruta directory");
         }
 
         // Setup .gitignore
@@ -182,11 +493,10 @@ Do not manually modify the files in this directory.
     }
 
     /// Setup .gitignore to exclude .ifm-ruta directory
-    fn setup_gitignore(&self, project_directory: &str) -> Result<(), ToolError> {
+    fn setup_gitignore(&self, project_directory: &Path) -> Result<(), ToolError> {
         use std::fs;
-        use std::path::Path;
 
-        let gitignore_path = Path::new(project_directory).join(".gitignore");
+        let gitignore_path = project_directory.join(".gitignore");
         let ifm_ruta_ignore = ".ifm-ruta/\n";
 
         if gitignore_path.exists() {
@@ -206,22 +516,23 @@ Do not manually modify the files in this directory.
                         message: format!("Failed to update .gitignore: {}", e),
                     }
                 })?;
-                println!("Added .ifm-ruta/ to existing .gitignore");
+                tracing::info!("Added .ifm-ruta/ to existing .gitignore");
             } else {
-                println!(".ifm-ruta/ already in .gitignore");
+                tracing::debug!(".ifm-ruta/ already in .gitignore");
             }
         } else {
             // Create new .gitignore
             fs::write(&gitignore_path, ifm_ruta_ignore).map_err(|e| ToolError::ExecutionError {
                 message: format!("Failed to create .gitignore: {}", e),
             })?;
-            println!("Created .gitignore with .ifm-ruta/ entry");
+            tracing::info!("Created .gitignore with .ifm-ruta/ entry");
         }
 
         Ok(())
     }
 }
 
+#[async_trait::async_trait]
 impl Tool for InteractiveFeedbackTool {
     fn name(&self) -> &str {
         "interactive_feedback"
@@ -246,20 +557,70 @@ impl Tool for InteractiveFeedbackTool {
                 "previousUserRequest": {
                     "type": "string",
                     "description": "The previous user request that triggered this interactive feedback"
+                },
+                "timeoutSeconds": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "Seconds to wait for the user before returning the default reply (0 waits forever, defaults to the configured timeout)"
+                },
+                "options": {
+                    "type": "array",
+                    "description": "Quick-reply choices shown as buttons above the text box",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "label": {
+                                "type": "string",
+                                "description": "Text of the choice, returned as selectedOption"
+                            },
+                            "description": {
+                                "type": "string",
+                                "description": "Extra explanation shown with the choice"
+                            },
+                            "default": {
+                                "type": "boolean",
+                                "description": "Preselect this choice and use it when the user does not answer in time"
+                            }
+                        },
+                        "required": ["label"]
+                    }
+                },
+                "allowFreeText": {
+                    "type": "boolean",
+                    "description": "Whether the user may type a reply instead of picking an option (default true)"
+                },
+                "form": {
+                    "type": "object",
+                    "description": "Fields to fill in, using the MCP elicitation requestedSchema subset: a flat object schema whose properties are string (optionally enum), number, integer or boolean, with optional title, description, default and required list. Values come back as formResponse",
+                    "properties": {
+                        "type": {
+                            "type": "string",
+                            "enum": ["object"]
+                        },
+                        "properties": {
+                            "type": "object"
+                        },
+                        "required": {
+                            "type": "array",
+                            "items": {
+                                "type": "string"
+                            }
+                        }
+                    },
+                    "required": ["properties"]
                 }
             },
             "required": ["projectDirectory", "prompt", "previousUserRequest"]
         })
     }
 
-    fn execute(&self, input: Value) -> Result<Value, ToolError> {
-        // Validate input
-        self.validate_input(&input)?;
-
-        // Extract parameters (matching Go schema)
+    async fn execute(&self, input: Value, context: &ToolContext) -> Result<Value, ToolError> {
+        // Extract parameters (matching Go schema); `validate_input` already
+        // canonicalized and checked the project directory
         let project_directory = input
             .get("projectDirectory")
             .and_then(|v| v.as_str())
+            .map(Path::new)
             .ok_or_else(|| ValidationError::MissingField {
                 field: "projectDirectory".to_string(),
             })?;
@@ -278,82 +639,284 @@ impl Tool for InteractiveFeedbackTool {
                 field: "previousUserRequest".to_string(),
             })?;
 
+        let started_at = Instant::now();
+        let settings = context.settings.get_settings();
+
+        // Per-call timeout falls back to the configured one; zero disables it
+        let timeout_seconds = input
+            .get("timeoutSeconds")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(settings.general.timeout.as_secs());
+
         // Save real conversation to storage
-        self.save_real_conversation(project_directory, previous_user_request, prompt)?;
+        let mut prompt_message =
+            self.save_real_conversation(project_directory, previous_user_request, prompt)?;
+
+        let mut request = FeedbackRequest::new(project_directory.to_path_buf(), prompt.to_string());
+        request.timeout_seconds = (timeout_seconds > 0).then_some(timeout_seconds);
+        if let Some(options) = input.get("options") {
+            request.options = serde_json::from_value::<Vec<FeedbackOption>>(options.clone())
+                .map_err(|e| ValidationError::InvalidInput {
+                    message: format!("Invalid options: {}", e),
+                })?;
+        }
+        if let Some(allow_free_text) = input.get("allowFreeText").and_then(|v| v.as_bool()) {
+            request.allow_free_text = allow_free_text;
+        }
+        if let Some(form) = input.get("form") {
+            request.form = Some(Self::parse_form(form)?);
+        }
 
         // Run interactive feedback with Python GUI like Go implementation
-        let user_feedback = self.run_interactive_feedback_with_gui(project_directory, prompt)?;
+        let mut feedback_result = match self
+            .run_interactive_feedback_with_gui(&request, context)
+            .await
+        {
+            Err(ToolError::Cancelled) => {
+                self.mark_round_cancelled(project_directory);
+                return Err(ToolError::Cancelled);
+            }
+            result => result?,
+        };
+
+        tracing::info!(
+            outcome = ?feedback_result.outcome,
+            interaction_ms = feedback_result.interaction_duration_ms,
+            elapsed_ms = started_at.elapsed().as_millis() as u64,
+            "Feedback round finished"
+        );
+
+        let timed_out = feedback_result.outcome == FeedbackOutcome::Timeout;
+        if timed_out {
+            // Let unattended agents carry on instead of treating silence as an error
+            feedback_result.text = settings.general.timeout_response;
+            feedback_result.selected_option =
+                request.default_option().map(|option| option.label.clone());
+        } else if feedback_result.outcome == FeedbackOutcome::Cancelled {
+            self.mark_round_cancelled(project_directory);
+            prompt_message.cancelled = true;
+        }
+
+        // A bare option pick still reads as an answer for agents that only look at the text
+        if feedback_result.text.is_empty() {
+            if let Some(selected) = &feedback_result.selected_option {
+                feedback_result.text = selected.clone();
+            }
+        }
 
-        // Create conversation history in Go format
-        let conversation_history =
-            self.create_conversation_history_format(previous_user_request, prompt)?;
+        // This round's prompt and, if the user answered, their reply as stored
+        let mut conversation_history = vec![prompt_message];
+        if feedback_result.outcome == FeedbackOutcome::Submitted {
+            conversation_history
+                .push(self.save_feedback_reply(project_directory, &feedback_result)?);
+        }
+
+        let attachments: Vec<Value> = feedback_result
+            .attachments
+            .iter()
+            .map(|attachment| {
+                json!({
+                    "path": attachment.path,
+                    "mimeType": attachment.mime_type
+                })
+            })
+            .collect();
+        let images = Self::image_content(&feedback_result.attachments)?;
 
         // Return the result in Go format
         Ok(json!({
-            "command_logs": "",
-            "interactive_feedback": user_feedback,
-            "conversation_history": conversation_history
+            "command_logs": feedback_result.command_logs,
+            "commands_executed": feedback_result.commands_executed,
+            "interactive_feedback": feedback_result.text,
+            "selectedOption": feedback_result.selected_option,
+            "formResponse": feedback_result.form_response,
+            "attachments": attachments,
+            "outcome": feedback_result.outcome,
+            "timedOut": timed_out,
+            "user_interaction_time_ms": feedback_result.interaction_duration_ms,
+            "conversation_history": conversation_history,
+            "conversationResource": session_uri(project_directory, CURRENT_SESSION_ID),
+            EXTRA_CONTENT_KEY: images
         }))
     }
 
-    fn validate_input(&self, input: &Value) -> Result<(), ValidationError> {
-        // Check required fields
-        if !input.is_object() {
-            return Err(ValidationError::InvalidInput {
+    /// Check the parts of the input its schema cannot express
+    ///
+    /// Types and required fields are checked by the server against
+    /// `input_schema` before this runs.
+    ///
+    /// `projectDirectory` is replaced by its canonical form, so `execute` can
+    /// use it as is.
+    fn validate_input(
+        &self,
+        input: &mut Value,
+        context: &ToolContext,
+    ) -> Result<(), ValidationError> {
+        let project_directory = self.project_directory(input, context)?;
+        let project_directory =
+            project_directory
+                .to_str()
+                .ok_or_else(|| ValidationError::InvalidInput {
+                    message: "projectDirectory: path is not valid UTF-8".to_string(),
+                })?;
+
+        let obj = input
+            .as_object_mut()
+            .ok_or_else(|| ValidationError::InvalidInput {
                 message: "Input must be an object".to_string(),
-            });
+            })?;
+        obj.insert("projectDirectory".to_string(), json!(project_directory));
+
+        // Check optional options
+        let option_count = match obj.get("options") {
+            Some(options) => self.validate_options(options)?,
+            None => 0,
+        };
+
+        // Check optional form
+        let has_form = match obj.get("form") {
+            Some(form) => {
+                Self::parse_form(form)?;
+                true
+            }
+            None => false,
+        };
+
+        // Check optional allowFreeText
+        if let Some(allow_free_text) = obj.get("allowFreeText") {
+            if allow_free_text.as_bool() == Some(false) && option_count == 0 && !has_form {
+                return Err(ValidationError::InvalidInput {
+                    message: "allowFreeText can only be false when options or a form are given"
+                        .to_string(),
+                });
+            }
         }
 
-        let obj = input.as_object().unwrap();
+        Ok(())
+    }
+}
 
-        // Check projectDirectory
-        if !obj.contains_key("projectDirectory") {
-            return Err(ValidationError::MissingField {
-                field: "projectDirectory".to_string(),
-            });
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ifm_ruta_core::services::{EventBusImpl, ProcessManagerImpl, SettingsManagerImpl};
+    use std::sync::Arc;
+
+    use crate::mcp::peer::{Peer, ProgressNotifier};
+    use crate::mcp::transport::ProtocolWriter;
+
+    fn validate(options: Value, allow_free_text: Option<bool>) -> Result<(), ValidationError> {
+        let context = ToolContext::new(
+            Arc::new(SettingsManagerImpl::new()),
+            Arc::new(ProcessManagerImpl::new()),
+            Arc::new(EventBusImpl::new()),
+        );
+        let mut input = json!({
+            "projectDirectory": std::env::temp_dir(),
+            "prompt": "Test prompt",
+            "previousUserRequest": "Test request",
+            "options": options
+        });
+        if let Some(allow_free_text) = allow_free_text {
+            input["allowFreeText"] = json!(allow_free_text);
         }
+        InteractiveFeedbackTool::new().validate_input(&mut input, &context)
+    }
 
-        if let Some(project_dir) = obj.get("projectDirectory") {
-            if !project_dir.is_string() {
-                return Err(ValidationError::InvalidType {
-                    field: "projectDirectory".to_string(),
-                    expected: "string".to_string(),
-                });
-            }
+    fn message(error: ValidationError) -> String {
+        match error {
+            ValidationError::InvalidInput { message } => message,
+            e => panic!("unexpected error: {:?}", e),
         }
+    }
 
-        // Check prompt
-        if !obj.contains_key("prompt") {
-            return Err(ValidationError::MissingField {
-                field: "prompt".to_string(),
-            });
+    #[test]
+    fn wait_progress_only_increases() {
+        let (writer, mut frames) = ProtocolWriter::recording();
+        let notifier = ProgressNotifier::new(Peer::new(writer), json!(7));
+        let reporter = ProgressReporter::new(Arc::new(notifier));
+
+        let mut progress = WaitProgress::new(&reporter);
+        progress.window_status(FeedbackStatus::Opened);
+        progress.keep_alive();
+        progress.window_status(FeedbackStatus::Typing);
+        progress.keep_alive();
+        progress.finished(FeedbackOutcome::Submitted);
+
+        let mut sent = Vec::new();
+        while let Ok(frame) = frames.try_recv() {
+            let frame: Value = serde_json::from_str(&frame).unwrap();
+            assert_eq!(frame["params"]["progressToken"], 7);
+            sent.push(frame["params"]["progress"].as_f64().unwrap());
         }
+        assert_eq!(sent.len(), 5);
+        assert!(sent.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", sent);
+    }
 
-        if let Some(prompt) = obj.get("prompt") {
-            if !prompt.is_string() {
-                return Err(ValidationError::InvalidType {
-                    field: "prompt".to_string(),
-                    expected: "string".to_string(),
-                });
-            }
-        }
+    #[test]
+    fn empty_options_are_accepted_with_free_text() {
+        assert!(validate(json!([]), None).is_ok());
+        assert!(validate(json!([]), Some(true)).is_ok());
+    }
 
-        // Check previousUserRequest
-        if !obj.contains_key("previousUserRequest") {
-            return Err(ValidationError::MissingField {
-                field: "previousUserRequest".to_string(),
-            });
-        }
+    #[test]
+    fn option_labels_must_be_unique_and_non_empty() {
+        let duplicate = validate(json!([{ "label": "Yes" }, { "label": "Yes" }]), None);
+        assert_eq!(
+            message(duplicate.unwrap_err()),
+            "options[1].label must be non-empty and unique"
+        );
+
+        let blank = validate(json!([{ "label": "  " }]), None);
+        assert_eq!(
+            message(blank.unwrap_err()),
+            "options[0].label must be non-empty and unique"
+        );
+
+        let defaults = validate(
+            json!([{ "label": "Yes", "default": true }, { "label": "No", "default": true }]),
+            None,
+        );
+        assert_eq!(
+            message(defaults.unwrap_err()),
+            "At most one option can be the default"
+        );
+    }
 
-        if let Some(previous_request) = obj.get("previousUserRequest") {
-            if !previous_request.is_string() {
-                return Err(ValidationError::InvalidType {
-                    field: "previousUserRequest".to_string(),
-                    expected: "string".to_string(),
-                });
-            }
-        }
+    #[test]
+    fn option_only_replies_need_options() {
+        assert!(validate(json!([{ "label": "Yes" }, { "label": "No" }]), Some(false)).is_ok());
 
-        Ok(())
+        let without_options = validate(json!([]), Some(false));
+        assert_eq!(
+            message(without_options.unwrap_err()),
+            "allowFreeText can only be false when options or a form are given"
+        );
+    }
+
+    #[test]
+    fn feedback_results_are_read_from_the_last_json_line() {
+        let stdout = format!(
+            "library noise\n{}\n",
+            json!({ "version": 1, "outcome": "submitted", "text": "Looks good", "interaction_duration_ms": 1200 })
+        );
+        let result = InteractiveFeedbackTool::parse_feedback_result(&stdout).unwrap();
+        assert_eq!(result.outcome, FeedbackOutcome::Submitted);
+        assert_eq!(result.text, "Looks good");
+
+        let error = InteractiveFeedbackTool::parse_feedback_result("library noise\n").unwrap_err();
+        assert!(error.contains("no feedback result"), "{}", error);
+    }
+
+    #[test]
+    fn feedback_results_from_another_version_are_refused() {
+        let stdout = json!({ "version": 2, "outcome": "submitted", "interaction_duration_ms": 0 })
+            .to_string();
+        let error = InteractiveFeedbackTool::parse_feedback_result(&stdout).unwrap_err();
+        assert!(
+            error.contains("feedback protocol version 2 is not supported"),
+            "{}",
+            error
+        );
     }
 }
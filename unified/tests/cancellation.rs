@@ -0,0 +1,87 @@
+//! Drops tool calls the client cancels, even when the cancel is handled first
+
+// The feedback window is kept from opening by removing the display variables,
+// which only works for X11/Wayland
+#![cfg(target_os = "linux")]
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+
+use serde_json::{json, Value};
+
+#[test]
+fn a_cancel_sent_right_after_the_call_drops_it() {
+    let dir = std::env::temp_dir().join(format!("ifm-ruta-cancellation-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ifm-ruta"))
+        .arg("--mcp-server")
+        .env_remove("DISPLAY")
+        .env_remove("WAYLAND_DISPLAY")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap()).lines();
+    let mut receive =
+        || -> Value { serde_json::from_str(&stdout.next().unwrap().unwrap()).unwrap() };
+
+    let initialize = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": { "capabilities": { "roots": {} } }
+    });
+    writeln!(stdin, "{}", initialize).unwrap();
+    assert_eq!(receive()["id"], 1);
+
+    // Both go out in one write, so the server may handle the cancel first
+    let call = json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": {
+            "name": "interactive_feedback",
+            "arguments": {
+                "projectDirectory": dir,
+                "prompt": "Test prompt",
+                "previousUserRequest": "Test request"
+            }
+        }
+    });
+    let cancel = json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/cancelled",
+        "params": { "requestId": 2, "reason": "changed my mind" }
+    });
+    write!(stdin, "{}\n{}\n", call, cancel).unwrap();
+    stdin.flush().unwrap();
+
+    // The call waits for the roots either way, so it cannot finish before the cancel
+    let roots_request = receive();
+    assert_eq!(roots_request["method"], "roots/list");
+    let answer = json!({ "jsonrpc": "2.0", "id": roots_request["id"], "result": { "roots": [] } });
+    writeln!(stdin, "{}", answer).unwrap();
+
+    // The cancelled call gets no response, so the next one seen is for this request
+    writeln!(
+        stdin,
+        "{}",
+        json!({ "jsonrpc": "2.0", "id": 3, "method": "tools/list" })
+    )
+    .unwrap();
+    let response = loop {
+        let frame = receive();
+        if frame.get("id").is_some() {
+            break frame;
+        }
+    };
+    assert_eq!(response["id"], 3, "{}", response);
+
+    drop(stdin);
+    let status = child.wait().unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+    assert!(status.success());
+}
@@ -0,0 +1,39 @@
+//! Checks that the feedback window refuses requests from another protocol version
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use serde_json::json;
+
+#[test]
+fn feedback_requests_with_another_version_are_refused() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ifm-ruta"))
+        .arg("--feedback-request")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let request = json!({
+        "version": 2,
+        "project_directory": std::env::temp_dir(),
+        "prompt": "Test prompt"
+    });
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(request.to_string().as_bytes())
+        .unwrap();
+
+    // Refused before any window opens
+    let output = child.wait_with_output().unwrap();
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("feedback protocol version 2 is not supported"),
+        "{}",
+        stderr
+    );
+}
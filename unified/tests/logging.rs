@@ -0,0 +1,121 @@
+//! Forwards log events at or above the client's level as `notifications/message`
+
+use std::io::{BufRead, BufReader, Lines, Write};
+use std::process::{ChildStdin, ChildStdout, Command, Stdio};
+
+use serde_json::{json, Value};
+
+/// Send a request and collect the frames up to its response, refusing any
+/// `roots/list` on the way, which the server logs as a warning
+fn request(
+    stdin: &mut ChildStdin,
+    stdout: &mut Lines<BufReader<ChildStdout>>,
+    id: u64,
+    method: &str,
+    params: Value,
+) -> Vec<Value> {
+    let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+    writeln!(stdin, "{}", message).unwrap();
+    let mut frames = Vec::new();
+    loop {
+        let frame: Value = serde_json::from_str(&stdout.next().unwrap().unwrap()).unwrap();
+        if frame["method"] == "roots/list" {
+            let answer = json!({
+                "jsonrpc": "2.0",
+                "id": frame["id"],
+                "error": { "code": -32601, "message": "Method not found" }
+            });
+            writeln!(stdin, "{}", answer).unwrap();
+            continue;
+        }
+        let is_response = frame["id"] == id;
+        frames.push(frame);
+        if is_response {
+            return frames;
+        }
+    }
+}
+
+#[test]
+fn log_events_reach_the_client_at_its_level() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ifm-ruta"))
+        .arg("--mcp-server")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut input = child.stdin.take().unwrap();
+    let mut output = BufReader::new(child.stdout.take().unwrap()).lines();
+    let (stdin, stdout) = (&mut input, &mut output);
+
+    let initialized = request(
+        stdin,
+        stdout,
+        1,
+        "initialize",
+        json!({ "capabilities": { "roots": {} } }),
+    );
+    assert!(initialized[0]["result"]["capabilities"]["logging"].is_object());
+
+    let set = request(
+        stdin,
+        stdout,
+        2,
+        "logging/setLevel",
+        json!({ "level": "warning" }),
+    );
+    assert_eq!(set[0]["result"], json!({}));
+
+    // Held back until the client reports that it is initialized
+    let frames = request(stdin, stdout, 3, "resources/list", json!({}));
+    assert_eq!(frames.len(), 1, "{:?}", frames);
+    writeln!(
+        stdin,
+        "{}",
+        json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })
+    )
+    .unwrap();
+    let message: Value = serde_json::from_str(&stdout.next().unwrap().unwrap()).unwrap();
+    assert_eq!(message["method"], "notifications/message");
+    assert_eq!(message["params"]["level"], "warning");
+    assert_eq!(
+        message["params"]["data"]["message"],
+        "Failed to list client roots"
+    );
+    assert!(
+        message["params"]["data"]["error"].is_string(),
+        "{}",
+        message
+    );
+
+    // Roots are asked for again after a change, but the warning is now below the level
+    let set = request(
+        stdin,
+        stdout,
+        4,
+        "logging/setLevel",
+        json!({ "level": "error" }),
+    );
+    assert_eq!(set[0]["result"], json!({}));
+    writeln!(
+        stdin,
+        "{}",
+        json!({ "jsonrpc": "2.0", "method": "notifications/roots/list_changed" })
+    )
+    .unwrap();
+    let frames = request(stdin, stdout, 5, "resources/list", json!({}));
+    assert_eq!(frames.len(), 1, "{:?}", frames);
+
+    let invalid = request(
+        stdin,
+        stdout,
+        6,
+        "logging/setLevel",
+        json!({ "level": "loud" }),
+    );
+    assert_eq!(invalid[0]["error"]["code"], -32602);
+
+    drop(input);
+    assert!(child.wait().unwrap().success());
+}
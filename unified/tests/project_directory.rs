@@ -0,0 +1,89 @@
+//! Checks that `projectDirectory` is confined to the client's roots
+
+// The feedback window is kept from opening by removing the display variables,
+// which only works for X11/Wayland
+#![cfg(target_os = "linux")]
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+
+use serde_json::{json, Value};
+
+fn call(id: u64, project_directory: &std::path::Path) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "tools/call",
+        "params": {
+            "name": "interactive_feedback",
+            "arguments": {
+                "projectDirectory": project_directory,
+                "prompt": "Test prompt",
+                "previousUserRequest": "Test request"
+            }
+        }
+    })
+}
+
+#[test]
+fn project_directories_outside_client_roots_are_rejected() {
+    let dir =
+        std::env::temp_dir().join(format!("ifm-ruta-project-directory-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("root")).unwrap();
+    std::fs::create_dir_all(dir.join("outside")).unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ifm-ruta"))
+        .arg("--mcp-server")
+        .env_remove("DISPLAY")
+        .env_remove("WAYLAND_DISPLAY")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap()).lines();
+    let mut send = |message: Value| writeln!(stdin, "{}", message).unwrap();
+    let mut receive =
+        || -> Value { serde_json::from_str(&stdout.next().unwrap().unwrap()).unwrap() };
+
+    send(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": { "capabilities": { "roots": { "listChanged": true } } }
+    }));
+    assert_eq!(receive()["id"], 1);
+
+    // The first tool call makes the server ask for the roots
+    send(call(2, &dir.join("root").join("..").join("outside")));
+    let roots_request = receive();
+    assert_eq!(roots_request["method"], "roots/list");
+    send(json!({
+        "jsonrpc": "2.0",
+        "id": roots_request["id"],
+        "result": {
+            "roots": [{ "uri": format!("file://{}", dir.join("root").display()), "name": "root" }]
+        }
+    }));
+
+    let response = receive();
+    assert_eq!(response["id"], 2);
+    assert_eq!(response["error"]["code"], -32602);
+    let message = response["error"]["message"].as_str().unwrap();
+    assert!(message.contains("projectDirectory"), "{}", message);
+    assert!(message.contains("outside the allowed roots"), "{}", message);
+
+    // The roots are remembered, so this is answered without asking again
+    send(call(3, &dir.join("root").join("missing")));
+    let response = receive();
+    assert_eq!(response["id"], 3);
+    assert_eq!(response["error"]["code"], -32602);
+    let message = response["error"]["message"].as_str().unwrap();
+    assert!(message.contains("does not exist"), "{}", message);
+
+    drop(stdin);
+    let status = child.wait().unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+    assert!(status.success());
+}
@@ -0,0 +1,139 @@
+//! Serves built-in, user and project prompt templates through `prompts/*`
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+
+use serde_json::{json, Value};
+
+#[test]
+#[cfg_attr(
+    not(target_os = "linux"),
+    ignore = "XDG_CONFIG_HOME only moves the user prompt directory on Linux"
+)]
+fn prompt_templates_are_listed_and_filled_in() {
+    let base = std::env::temp_dir().join(format!("ifm-ruta-prompts-{}", std::process::id()));
+    let config_dir = base.join("config");
+    let project_dir = base.join("project");
+    std::fs::create_dir_all(config_dir.join("ifm-ruta").join("prompts")).unwrap();
+    std::fs::create_dir_all(project_dir.join(".ifm-ruta").join("prompts")).unwrap();
+    let project_dir = project_dir.canonicalize().unwrap();
+
+    std::fs::write(
+        config_dir
+            .join("ifm-ruta")
+            .join("prompts")
+            .join("standup.md"),
+        "Call interactive_feedback with what you did today.",
+    )
+    .unwrap();
+    std::fs::write(
+        project_dir
+            .join(".ifm-ruta")
+            .join("prompts")
+            .join("plan-approval.md"),
+        "---\ndescription: Our team's plan review\narguments:\n  - name: task\n    required: true\n---\nPlan first: {{task}}\n",
+    )
+    .unwrap();
+    let root_uri = format!("file://{}", project_dir.display());
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ifm-ruta"))
+        .arg("--mcp-server")
+        .env("XDG_CONFIG_HOME", &config_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap()).lines();
+    let mut request = |id: u64, method: &str, params: Value| -> Value {
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        writeln!(stdin, "{}", message).unwrap();
+        loop {
+            let frame: Value = serde_json::from_str(&stdout.next().unwrap().unwrap()).unwrap();
+            if frame["method"] == "roots/list" {
+                let answer = json!({
+                    "jsonrpc": "2.0",
+                    "id": frame["id"],
+                    "result": { "roots": [{ "uri": root_uri }] }
+                });
+                writeln!(stdin, "{}", answer).unwrap();
+                continue;
+            }
+            assert_eq!(frame["id"], id);
+            return frame;
+        }
+    };
+
+    let initialized = request(1, "initialize", json!({ "capabilities": { "roots": {} } }));
+    assert!(initialized["result"]["capabilities"]["prompts"].is_object());
+
+    let listed = request(2, "prompts/list", json!({}));
+    let prompts = listed["result"]["prompts"].as_array().unwrap();
+    let names: Vec<&str> = prompts
+        .iter()
+        .map(|prompt| prompt["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "ask-before-destructive-change",
+            "plan-approval",
+            "review-loop",
+            "standup"
+        ]
+    );
+    // The project's template replaces the built-in one
+    assert_eq!(prompts[1]["description"], "Our team's plan review");
+    assert_eq!(prompts[2]["arguments"][0]["name"], "task");
+    assert_eq!(prompts[2]["arguments"][0]["required"], true);
+
+    let review = request(
+        3,
+        "prompts/get",
+        json!({ "name": "review-loop", "arguments": { "task": "Fix the login bug" } }),
+    );
+    let message = &review["result"]["messages"][0];
+    assert_eq!(message["role"], "user");
+    let text = message["content"]["text"].as_str().unwrap();
+    assert!(text.contains("Fix the login bug"), "{}", text);
+    assert!(text.contains("interactive_feedback"), "{}", text);
+
+    // Optional arguments fall back to their default
+    let destructive = request(
+        4,
+        "prompts/get",
+        json!({ "name": "ask-before-destructive-change" }),
+    );
+    let text = destructive["result"]["messages"][0]["content"]["text"]
+        .as_str()
+        .unwrap();
+    assert!(text.contains("force-pushing"), "{}", text);
+    assert!(!text.contains("{{"), "{}", text);
+
+    let plan = request(
+        5,
+        "prompts/get",
+        json!({ "name": "plan-approval", "arguments": { "task": "Add caching" } }),
+    );
+    assert_eq!(
+        plan["result"]["messages"][0]["content"]["text"],
+        "Plan first: Add caching"
+    );
+
+    let standup = request(6, "prompts/get", json!({ "name": "standup" }));
+    assert_eq!(
+        standup["result"]["messages"][0]["content"]["text"],
+        "Call interactive_feedback with what you did today."
+    );
+
+    let missing_argument = request(7, "prompts/get", json!({ "name": "review-loop" }));
+    assert_eq!(missing_argument["error"]["code"], -32602);
+    let unknown = request(8, "prompts/get", json!({ "name": "no-such-prompt" }));
+    assert_eq!(unknown["error"]["code"], -32602);
+
+    drop(stdin);
+    let status = child.wait().unwrap();
+    let _ = std::fs::remove_dir_all(&base);
+    assert!(status.success());
+}
@@ -0,0 +1,122 @@
+//! Subscribers hear about conversation changes made by other processes
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{ChildStdin, Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use ifm_ruta_core::services::ConversationStorage;
+use serde_json::{json, Value};
+
+#[test]
+fn subscribers_are_notified_of_new_messages() {
+    let project_dir = std::env::temp_dir()
+        .join(format!("ifm-ruta-subscriptions-{}", std::process::id()))
+        .join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    let project_dir = project_dir.canonicalize().unwrap();
+    let storage = ConversationStorage::new(&project_dir);
+    storage
+        .add_message("current-conversation", "assistant", "Ready for review")
+        .unwrap();
+    let root_uri = format!("file://{}", project_dir.display());
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ifm-ruta"))
+        .arg("--mcp-server")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+
+    // Read frames on a thread so waiting for a notification can time out
+    let (frames, received) = mpsc::channel::<Value>();
+    let stdout = child.stdout.take().unwrap();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else { break };
+            if frames.send(serde_json::from_str(&line).unwrap()).is_err() {
+                break;
+            }
+        }
+    });
+    let next_frame = |stdin: &mut ChildStdin| -> Value {
+        loop {
+            let frame = received
+                .recv_timeout(Duration::from_secs(10))
+                .expect("no frame from the server");
+            if frame["method"] == "roots/list" {
+                let answer = json!({
+                    "jsonrpc": "2.0",
+                    "id": frame["id"],
+                    "result": { "roots": [{ "uri": root_uri }] }
+                });
+                writeln!(stdin, "{}", answer).unwrap();
+                continue;
+            }
+            return frame;
+        }
+    };
+    let request = |stdin: &mut ChildStdin, id: u64, method: &str, params: Value| -> Value {
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        writeln!(stdin, "{}", message).unwrap();
+        loop {
+            let frame = next_frame(stdin);
+            // A save can be reported more than once
+            if frame["method"] == "notifications/resources/updated" {
+                continue;
+            }
+            assert_eq!(frame["id"], id, "{}", frame);
+            return frame;
+        }
+    };
+
+    let initialized = request(
+        &mut stdin,
+        1,
+        "initialize",
+        json!({ "capabilities": { "roots": {} } }),
+    );
+    assert_eq!(
+        initialized["result"]["capabilities"]["resources"]["subscribe"],
+        true
+    );
+
+    let listed = request(&mut stdin, 2, "resources/list", json!({}));
+    let uri = listed["result"]["resources"][0]["uri"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let subscribed = request(&mut stdin, 3, "resources/subscribe", json!({ "uri": uri }));
+    assert_eq!(subscribed["result"], json!({}), "{}", subscribed);
+
+    let missing = request(
+        &mut stdin,
+        4,
+        "resources/subscribe",
+        json!({ "uri": "ifm-ruta://other/sessions/current-conversation" }),
+    );
+    assert_eq!(missing["error"]["code"], -32002, "{}", missing);
+
+    // The user answers from the feedback window, a separate process
+    storage
+        .add_message("current-conversation", "user", "Looks good")
+        .unwrap();
+    let updated = next_frame(&mut stdin);
+    assert_eq!(updated["method"], "notifications/resources/updated");
+    assert_eq!(updated["params"]["uri"], uri);
+
+    let unsubscribed = request(
+        &mut stdin,
+        5,
+        "resources/unsubscribe",
+        json!({ "uri": uri }),
+    );
+    assert_eq!(unsubscribed["result"], json!({}), "{}", unsubscribed);
+
+    drop(stdin);
+    let status = child.wait().unwrap();
+    let _ = std::fs::remove_dir_all(project_dir.parent().unwrap());
+    assert!(status.success());
+}
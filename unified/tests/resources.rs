@@ -0,0 +1,177 @@
+//! Reads stored conversations through `resources/*`
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+
+use ifm_ruta_core::services::ConversationStorage;
+use serde_json::{json, Value};
+
+#[test]
+fn conversations_are_listed_and_read_as_resources() {
+    let project_dir = std::env::temp_dir()
+        .join(format!("ifm-ruta-resources-{}", std::process::id()))
+        .join("my project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    let project_dir = project_dir.canonicalize().unwrap();
+    let storage = ConversationStorage::new(&project_dir);
+    storage
+        .add_message("current-conversation", "assistant", "Ship it?")
+        .unwrap();
+    storage
+        .add_message("current-conversation", "user", "Yes, after the tests pass")
+        .unwrap();
+
+    let root_uri = format!(
+        "file://{}",
+        project_dir.display().to_string().replace(' ', "%20")
+    );
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ifm-ruta"))
+        .arg("--mcp-server")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap()).lines();
+    let mut request = |id: u64, method: &str, params: Value| -> Value {
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        writeln!(stdin, "{}", message).unwrap();
+        loop {
+            let frame: Value = serde_json::from_str(&stdout.next().unwrap().unwrap()).unwrap();
+            if frame["method"] == "roots/list" {
+                // The project is known to the server through the client's roots
+                let answer = json!({
+                    "jsonrpc": "2.0",
+                    "id": frame["id"],
+                    "result": { "roots": [{ "uri": root_uri }] }
+                });
+                writeln!(stdin, "{}", answer).unwrap();
+                continue;
+            }
+            assert_eq!(frame["id"], id);
+            return frame;
+        }
+    };
+
+    let initialized = request(1, "initialize", json!({ "capabilities": { "roots": {} } }));
+    assert!(initialized["result"]["capabilities"]["resources"].is_object());
+
+    let listed = request(2, "resources/list", json!({}));
+    let resources = listed["result"]["resources"].as_array().unwrap();
+    assert_eq!(resources.len(), 1, "{}", listed);
+    let uri = resources[0]["uri"].as_str().unwrap().to_string();
+    let project = uri
+        .strip_prefix("ifm-ruta://")
+        .and_then(|rest| rest.strip_suffix("/sessions/current-conversation"))
+        .unwrap();
+    assert!(project.starts_with("my%20project-"), "{}", uri);
+    assert_eq!(resources[0]["mimeType"], "text/markdown");
+
+    let markdown = request(3, "resources/read", json!({ "uri": uri }));
+    let text = markdown["result"]["contents"][0]["text"].as_str().unwrap();
+    assert!(text.contains("## Assistant"), "{}", text);
+    assert!(text.contains("Yes, after the tests pass"), "{}", text);
+
+    let stored = request(
+        4,
+        "resources/read",
+        json!({ "uri": format!("{}?format=json", uri) }),
+    );
+    let content = &stored["result"]["contents"][0];
+    assert_eq!(content["mimeType"], "application/json");
+    let session: Value = serde_json::from_str(content["text"].as_str().unwrap()).unwrap();
+    assert_eq!(session["messages"].as_array().unwrap().len(), 2);
+
+    let templates = request(5, "resources/templates/list", json!({}));
+    assert_eq!(
+        templates["result"]["resourceTemplates"][0]["uriTemplate"],
+        "ifm-ruta://{project}/sessions/{id}"
+    );
+
+    for (id, missing) in [
+        (6, format!("ifm-ruta://{}/sessions/other", project)),
+        (
+            7,
+            "ifm-ruta://my%20project/sessions/current-conversation".to_string(),
+        ),
+        (
+            8,
+            format!("ifm-ruta://{}/sessions/..%2F..%2Fsecret", project),
+        ),
+    ] {
+        let response = request(id, "resources/read", json!({ "uri": missing }));
+        assert_eq!(response["error"]["code"], -32002, "{}", response);
+    }
+
+    drop(stdin);
+    let status = child.wait().unwrap();
+    let _ = std::fs::remove_dir_all(project_dir.parent().unwrap());
+    assert!(status.success());
+}
+
+#[test]
+fn projects_with_the_same_name_stay_apart() {
+    let base = std::env::temp_dir().join(format!("ifm-ruta-same-name-{}", std::process::id()));
+    let mut roots = Vec::new();
+    for (parent, answer) in [("a", "Answer from a"), ("b", "Answer from b")] {
+        let project_dir = base.join(parent).join("app");
+        std::fs::create_dir_all(&project_dir).unwrap();
+        let project_dir = project_dir.canonicalize().unwrap();
+        ConversationStorage::new(&project_dir)
+            .add_message("current-conversation", "user", answer)
+            .unwrap();
+        roots.push(json!({ "uri": format!("file://{}", project_dir.display()) }));
+    }
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ifm-ruta"))
+        .arg("--mcp-server")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap()).lines();
+    let mut request = |id: u64, method: &str, params: Value| -> Value {
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        writeln!(stdin, "{}", message).unwrap();
+        loop {
+            let frame: Value = serde_json::from_str(&stdout.next().unwrap().unwrap()).unwrap();
+            if frame["method"] == "roots/list" {
+                let answer = json!({
+                    "jsonrpc": "2.0",
+                    "id": frame["id"],
+                    "result": { "roots": roots }
+                });
+                writeln!(stdin, "{}", answer).unwrap();
+                continue;
+            }
+            assert_eq!(frame["id"], id);
+            return frame;
+        }
+    };
+
+    request(1, "initialize", json!({ "capabilities": { "roots": {} } }));
+    let listed = request(2, "resources/list", json!({}));
+    let uris: Vec<String> = listed["result"]["resources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|resource| resource["uri"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(uris.len(), 2, "{}", listed);
+    assert_ne!(uris[0], uris[1]);
+
+    for (id, (uri, answer)) in (3..).zip(uris.iter().zip(["Answer from a", "Answer from b"])) {
+        let read = request(id, "resources/read", json!({ "uri": uri }));
+        let text = read["result"]["contents"][0]["text"].as_str().unwrap();
+        assert!(text.contains(answer), "{} -> {}", uri, text);
+    }
+
+    drop(stdin);
+    let status = child.wait().unwrap();
+    let _ = std::fs::remove_dir_all(&base);
+    assert!(status.success());
+}
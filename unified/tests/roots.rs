@@ -0,0 +1,63 @@
+//! Stops asking for the client's roots once it fails to answer
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+#[test]
+fn unanswered_roots_requests_are_not_repeated_until_the_roots_change() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ifm-ruta"))
+        .arg("--mcp-server")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap()).lines();
+    let mut send = |message: Value| writeln!(stdin, "{}", message).unwrap();
+    let mut receive =
+        || -> Value { serde_json::from_str(&stdout.next().unwrap().unwrap()).unwrap() };
+
+    send(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": { "capabilities": { "roots": { "listChanged": true } } }
+    }));
+    assert_eq!(receive()["id"], 1);
+    send(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }));
+
+    // The client never answers, so the request waits for the server to give up
+    send(json!({ "jsonrpc": "2.0", "id": 2, "method": "resources/list" }));
+    let roots_request = receive();
+    assert_eq!(roots_request["method"], "roots/list");
+    let listed = loop {
+        let frame = receive();
+        if frame["id"] == 2 {
+            break frame;
+        }
+        assert_eq!(frame["method"], "notifications/message", "{}", frame);
+    };
+    assert!(listed["result"]["resources"].is_array(), "{}", listed);
+
+    // Later requests go without roots instead of waiting again
+    let started = Instant::now();
+    send(json!({ "jsonrpc": "2.0", "id": 3, "method": "resources/list" }));
+    let listed = receive();
+    assert_eq!(listed["id"], 3, "{}", listed);
+    assert!(started.elapsed() < Duration::from_secs(5));
+
+    // A reported change makes the server ask again
+    send(json!({ "jsonrpc": "2.0", "method": "notifications/roots/list_changed" }));
+    send(json!({ "jsonrpc": "2.0", "id": 4, "method": "resources/list" }));
+    let roots_request = receive();
+    assert_eq!(roots_request["method"], "roots/list");
+    send(json!({ "jsonrpc": "2.0", "id": roots_request["id"], "result": { "roots": [] } }));
+    assert_eq!(receive()["id"], 4);
+
+    drop(stdin);
+    assert!(child.wait().unwrap().success());
+}
@@ -0,0 +1,126 @@
+//! Checks that server mode writes nothing but JSON-RPC frames to stdout
+
+// The feedback window is kept from opening by removing the display variables,
+// which only works for X11/Wayland
+#![cfg(target_os = "linux")]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use serde_json::Value;
+
+#[test]
+fn stdout_only_carries_json_rpc_frames() {
+    let project_dir =
+        std::env::temp_dir().join(format!("ifm-ruta-stdout-hygiene-{}", std::process::id()));
+    std::fs::create_dir_all(&project_dir).unwrap();
+
+    let call = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 5,
+        "method": "tools/call",
+        "params": {
+            "name": "interactive_feedback",
+            "arguments": {
+                "projectDirectory": project_dir,
+                "prompt": "Test prompt",
+                "previousUserRequest": "Test request"
+            }
+        }
+    });
+    let session = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#.to_string(),
+        r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#.to_string(),
+        r#"{"jsonrpc":"2.0","id":2,"method":"ping"}"#.to_string(),
+        r#"{"jsonrpc":"2.0","id":3,"method":"tools/list"}"#.to_string(),
+        "not json".to_string(),
+        r#"{"jsonrpc":"2.0","id":4,"method":"unknown/method"}"#.to_string(),
+        call.to_string(),
+    ];
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ifm-ruta"))
+        .arg("--mcp-server")
+        .env_remove("DISPLAY")
+        .env_remove("WAYLAND_DISPLAY")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    {
+        let mut stdin = child.stdin.take().unwrap();
+        for line in &session {
+            writeln!(stdin, "{}", line).unwrap();
+        }
+    }
+
+    let output = child.wait_with_output().unwrap();
+    let _ = std::fs::remove_dir_all(&project_dir);
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let frames: Vec<Value> = stdout
+        .lines()
+        .map(|line| {
+            serde_json::from_str(line)
+                .unwrap_or_else(|e| panic!("stdout line is not JSON ({}): {:?}", e, line))
+        })
+        .collect();
+
+    for frame in &frames {
+        assert_eq!(frame["jsonrpc"], "2.0", "not a JSON-RPC frame: {}", frame);
+        assert!(
+            frame.get("result").is_some()
+                || frame.get("error").is_some()
+                || frame.get("method").is_some(),
+            "not a JSON-RPC frame: {}",
+            frame
+        );
+    }
+
+    // One response per request plus the parse error; no response to the notification
+    let responses = frames.iter().filter(|f| f.get("method").is_none()).count();
+    assert_eq!(responses, 6);
+}
+
+#[test]
+fn invalid_utf8_lines_get_parse_errors_without_ending_the_session() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ifm-ruta"))
+        .arg("--mcp-server")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    {
+        let mut stdin = child.stdin.take().unwrap();
+        stdin
+            .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"ping\"}\n")
+            .unwrap();
+        stdin.write_all(b"\xff\xfe not utf-8\n").unwrap();
+        stdin
+            .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":2,\"method\":\"ping\"}\n")
+            .unwrap();
+    }
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let frames: Vec<Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .filter(|frame| frame.get("method").is_none())
+        .collect();
+    assert_eq!(frames.len(), 3);
+    assert!(frames
+        .iter()
+        .any(|frame| frame["error"]["code"] == -32700 && frame["id"].is_null()));
+    for id in [1, 2] {
+        assert!(frames
+            .iter()
+            .any(|frame| frame["id"] == id && frame.get("result").is_some()));
+    }
+}
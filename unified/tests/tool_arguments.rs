@@ -0,0 +1,91 @@
+//! Checks that tool arguments are validated against the tool's input schema
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use serde_json::{json, Value};
+
+#[test]
+fn schema_violations_are_reported_with_pointers() {
+    let call = |id: u64, arguments: Value| {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": { "name": "interactive_feedback", "arguments": arguments }
+        })
+    };
+    let session = [
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+        call(
+            2,
+            json!({ "projectDirectory": "/", "previousUserRequest": "Test request" }),
+        ),
+        call(
+            3,
+            json!({
+                "projectDirectory": "/",
+                "prompt": "Test prompt",
+                "previousUserRequest": "Test request",
+                "timeoutSeconds": -1
+            }),
+        ),
+        call(
+            4,
+            json!({
+                "projectDirectory": "/",
+                "prompt": "Test prompt",
+                "previousUserRequest": "Test request",
+                "options": [{ "label": "Yes" }, { "description": "No label" }]
+            }),
+        ),
+    ];
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ifm-ruta"))
+        .arg("--mcp-server")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    {
+        let mut stdin = child.stdin.take().unwrap();
+        for message in &session {
+            writeln!(stdin, "{}", message).unwrap();
+        }
+    }
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let responses: Vec<Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let error = |id: u64| {
+        let response = responses
+            .iter()
+            .find(|response| response["id"] == id)
+            .unwrap_or_else(|| panic!("no response to {}", id));
+        assert_eq!(response["error"]["code"], -32602, "{}", response);
+        response["error"].clone()
+    };
+
+    assert_eq!(
+        error(2)["data"],
+        json!({ "pointer": "/prompt", "keyword": "required" })
+    );
+    let timeout = error(3);
+    assert_eq!(
+        timeout["data"],
+        json!({ "pointer": "/timeoutSeconds", "keyword": "minimum" })
+    );
+    assert_eq!(
+        timeout["message"],
+        "Invalid value at `/timeoutSeconds`: must be at least 0"
+    );
+    assert_eq!(
+        error(4)["data"],
+        json!({ "pointer": "/options/1/label", "keyword": "required" })
+    );
+}